@@ -0,0 +1,96 @@
+use crate::offline_to_return;
+use crate::runtime::{OFFLINE, get_client, get_config};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering::Relaxed;
+use tracing::{debug, info, warn};
+
+pub async fn login(device_name: Option<&String>) {
+    offline_to_return!();
+
+    let (Some(username), Some(password)) = (
+        get_config().username.as_ref(),
+        get_config().password.as_ref(),
+    ) else {
+        warn!("Username and password are needed to create a token");
+        return;
+    };
+
+    let device_name = match device_name {
+        Some(device_name) => device_name.clone(),
+        None => get_device_name(),
+    };
+    let Some(auth_token) = get_client()
+        .create_token(username, password, &device_name)
+        .await
+    else {
+        return;
+    };
+
+    match get_client_config_path() {
+        Some(config_path) => {
+            store_token_in_config(config_path.as_path(), &auth_token.token);
+            info!(
+                "Created token {} for {device_name} and stored it in {}",
+                auth_token.id,
+                config_path.display()
+            );
+        }
+        None => info!("Created token {}: {}", auth_token.id, auth_token.token),
+    }
+}
+
+pub async fn list_tokens() {
+    offline_to_return!();
+
+    for token in get_client().get_tokens().await.unwrap_or_default() {
+        info!("{token}");
+    }
+}
+
+pub async fn revoke_token(id: &str) {
+    offline_to_return!();
+
+    if get_client().revoke_token(id).await {
+        info!("Revoked token {id}");
+    }
+}
+
+pub fn get_device_name() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "minus_games_client".to_string())
+}
+
+fn get_client_config_path() -> Option<PathBuf> {
+    Some(
+        dirs::config_local_dir()?
+            .join("minus_games_client")
+            .join("config"),
+    )
+}
+
+/// Replaces the stored password of a dotenv config file with the token.
+pub fn store_token_in_config(config_path: &std::path::Path, token: &str) {
+    let content = std::fs::read_to_string(config_path).unwrap_or_default();
+    let mut lines: Vec<&str> = content
+        .lines()
+        .filter(|line| {
+            !line.starts_with("MINUS_GAMES_PASSWORD=") && !line.starts_with("MINUS_GAMES_TOKEN=")
+        })
+        .collect();
+    let token_line = format!("MINUS_GAMES_TOKEN=\"{token}\"");
+    lines.push(token_line.as_str());
+
+    if let Some(parent) = config_path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    let result = std::fs::File::create(config_path)
+        .and_then(|mut file| file.write_all(format!("{}\n", lines.join("\n")).as_bytes()));
+    if let Err(err) = result {
+        warn!(
+            "Failed to store token at: {} with {err}",
+            config_path.display()
+        );
+    }
+}
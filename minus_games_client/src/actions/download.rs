@@ -34,10 +34,8 @@ pub async fn download_all_files(game: &str) {
         Some(file_list) => {
             let mut download_configs = Vec::with_capacity(file_list.len());
             for file in file_list {
-                let dc = DownloadConfig::new(
-                    file.generate_download_link(get_config().server_url.as_str()),
-                    file.file_path.clone(),
-                );
+                let dc =
+                    DownloadConfig::from_game_file_info(file, get_config().server_url.as_str());
                 download_configs.push(dc);
             }
 
@@ -153,7 +153,7 @@ pub async fn select_check_for_corruption() {
 
     if let Some(selection) = selection {
         let game = installed_games.get(selection).unwrap();
-        check_for_corruption_for_game(game).await;
+        check_for_corruption_for_game(game, false, false).await;
     } else {
         info!("Nothing selected!")
     }
@@ -1,3 +1,4 @@
+pub mod auth;
 pub mod delete;
 pub mod menu;
 pub mod other;
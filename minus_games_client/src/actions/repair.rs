@@ -1,9 +1,14 @@
 use crate::actions::sync::force_sync_all_game_files;
+use crate::download_manager::{DownloadConfig, DownloadManager, PART_EXTENSION};
 use crate::runtime::{
     MinusGamesClientEvents, STOP_DOWNLOAD, get_config, get_installed_games, send_event,
 };
+use crate::utils::get_blake3_hash;
 use log::info;
-use std::fs::File;
+use minus_games_utils::create_file_list;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::process::ExitCode;
 use std::sync::atomic::Ordering::Relaxed;
 use tracing::warn;
 
@@ -14,82 +19,186 @@ pub async fn repair_game(game: &str) {
     send_event(MinusGamesClientEvents::FinishedSyncFileInfos).await;
 }
 
-pub async fn check_for_corruption_for_game(game: &str) {
+#[derive(Serialize, Default, Debug)]
+pub struct CorruptionReport {
+    pub game: String,
+    pub missing: Vec<String>,
+    pub wrong_size: Vec<String>,
+    pub wrong_hash: Vec<String>,
+    pub extra: Vec<String>,
+}
+
+impl CorruptionReport {
+    pub fn is_corrupt(&self) -> bool {
+        !self.missing.is_empty() || !self.wrong_size.is_empty() || !self.wrong_hash.is_empty()
+    }
+
+    fn is_bad_file(&self, file_path: &String) -> bool {
+        self.missing.contains(file_path)
+            || self.wrong_size.contains(file_path)
+            || self.wrong_hash.contains(file_path)
+    }
+
+    fn log(&self) {
+        for file in self.missing.iter() {
+            warn!("Missing File: {file}");
+        }
+        for file in self.wrong_size.iter() {
+            warn!("Wrong size for File: {file}");
+        }
+        for file in self.wrong_hash.iter() {
+            warn!("Wrong hash for File: {file}");
+        }
+        for file in self.extra.iter() {
+            info!("File not in the file list: {file}");
+        }
+        info!(
+            "Game {}: {} missing, {} wrong size, {} wrong hash, {} extra files",
+            self.game,
+            self.missing.len(),
+            self.wrong_size.len(),
+            self.wrong_hash.len(),
+            self.extra.len()
+        );
+    }
+}
+
+/// Fails, if corrupt files remain after the check or the fix.
+pub async fn check_for_corruption_for_game(game: &str, fix: bool, json: bool) -> ExitCode {
     STOP_DOWNLOAD.store(false, Relaxed);
     send_event(MinusGamesClientEvents::CurrentGame(game.to_string())).await;
-    check_game_for_corrupt_files(game).await;
+    if !json {
+        info!("Check for corrupted Files for game {game}");
+    }
+    let report = match check_game_for_corrupt_files(game).await {
+        Some(report) => report,
+        None => {
+            warn!("Game File List not found for game {game}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        report.log();
+    }
+
+    let mut is_corrupt = report.is_corrupt();
+    if fix && is_corrupt {
+        fix_corrupt_files(&report).await;
+        is_corrupt = check_game_for_corrupt_files(game)
+            .await
+            .is_none_or(|report| report.is_corrupt());
+        if is_corrupt {
+            warn!("Game {game} still has corrupt files");
+        }
+    }
     send_event(MinusGamesClientEvents::FinishedSyncFileInfos).await;
+    if is_corrupt {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
 }
 
-async fn check_game_for_corrupt_files(game: &str) {
-    info!("Check for corrupted Files for game {game}");
-    let game_file_infos = get_config()
-        .get_game_file_list(game)
-        .expect("Game File List not found");
-    for info in game_file_infos {
-        let file_path = get_config().client_games_folder.join(info.file_path);
-
-        if !file_path.is_file() {
-            warn!(
-                "Did not find File: {} with Filesize: {} and Hash: {}",
-                file_path.display(),
-                info.size,
-                info.hash
-            );
+pub async fn check_game_for_corrupt_files(game: &str) -> Option<CorruptionReport> {
+    let game_file_infos = get_config().get_game_file_list(game)?;
+    let mut report = CorruptionReport {
+        game: game.to_string(),
+        ..Default::default()
+    };
+
+    let to_check = game_file_infos.clone();
+    let hash_mismatches = tokio::task::spawn_blocking(move || {
+        let mut rtn = Vec::new();
+        for info in to_check {
+            let file_path = get_config().client_games_folder.join(&info.file_path);
+            if let Ok(metadata) = file_path.metadata()
+                && metadata.len() == info.size
+                && get_blake3_hash(file_path.as_path()).as_ref() != Some(&info.hash)
+            {
+                rtn.push(info.file_path);
+            }
         }
+        rtn
+    })
+    .await
+    .unwrap_or_default();
 
-        let metadata = match file_path.as_path().metadata() {
-            Ok(metadata) => metadata,
-            Err(err) => {
-                warn!(
-                    "Failed to get the metadata for File: {} with Filesize: {} and Hash: {} with Error: {}",
-                    file_path.display(),
-                    info.size,
-                    info.hash,
-                    err
-                );
-                continue;
+    for info in game_file_infos.iter() {
+        let file_path = get_config().client_games_folder.join(&info.file_path);
+        match file_path.metadata() {
+            Ok(metadata) if metadata.is_file() => {
+                if metadata.len() != info.size {
+                    report.wrong_size.push(info.file_path.clone());
+                }
             }
-        };
-
-        if metadata.len() != info.size {
-            warn!(
-                "Wrong size for File: {} with Filesize: {} and Hash: {}",
-                file_path.display(),
-                info.size,
-                info.hash,
-            );
-            continue;
+            _ => report.missing.push(info.file_path.clone()),
         }
-        let file = match File::open(&file_path) {
-            Ok(value) => value,
-            Err(err) => {
-                warn!(
-                    "Wrong size for File: {} with Filesize: {}, Hash: {} and Error {}",
-                    file_path.display(),
-                    info.size,
-                    info.hash,
-                    err
-                );
-                continue;
+    }
+    report.wrong_hash = hash_mismatches;
+
+    let known_files: HashSet<&str> = game_file_infos
+        .iter()
+        .map(|info| info.file_path.as_str())
+        .collect();
+    let game_path = get_config().get_game_path(game);
+    if game_path.is_dir() {
+        let games_folder = std::path::absolute(get_config().client_games_folder.as_path()).ok()?;
+        for file in create_file_list(game_path.as_path()) {
+            let relative_path = match file.strip_prefix(games_folder.as_path()) {
+                Ok(relative_path) => relative_path
+                    .iter()
+                    .map(|part| part.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                Err(_) => continue,
+            };
+            // Partial downloads are resumed by the next download
+            if !known_files.contains(relative_path.as_str())
+                && !relative_path.ends_with(PART_EXTENSION)
+            {
+                report.extra.push(relative_path);
             }
-        };
-        let file_hash = blake3::Hasher::new()
-            .update_reader(file)
-            .unwrap()
-            .finalize()
-            .to_string();
-        if file_hash != info.hash {
-            warn!(
-                "Wrong hash for File: {} with Filesize: {} and Hash: {} while current Filehash: {}",
-                file_path.display(),
-                info.size,
-                info.hash,
-                file_hash
-            );
+        }
+    }
+
+    Some(report)
+}
+
+async fn fix_corrupt_files(report: &CorruptionReport) {
+    let game_file_infos = match get_config().get_game_file_list(&report.game) {
+        Some(game_file_infos) => game_file_infos,
+        None => return,
+    };
+
+    let mut download_configs = Vec::new();
+    for info in game_file_infos {
+        if !report.is_bad_file(&info.file_path) {
+            continue;
+        }
+        let file_path = get_config().client_games_folder.join(&info.file_path);
+        if file_path.is_file()
+            && let Err(err) = std::fs::remove_file(file_path.as_path())
+        {
+            warn!("Failed to delete the file: {} - {err}", file_path.display());
             continue;
         }
+        download_configs.push(DownloadConfig::from_game_file_info(
+            info,
+            get_config().server_url.as_str(),
+        ));
     }
+
+    info!(
+        "Download {} corrupt files for game {}",
+        download_configs.len(),
+        report.game
+    );
+    DownloadManager::with(download_configs)
+        .download_all_to(get_config().client_games_folder.as_path())
+        .await;
 }
 
 pub async fn repair_all_games() {
@@ -7,13 +7,17 @@ use crate::runtime::{
 use crate::utils::{add_permissions, is_not_executable, make_executable};
 #[cfg(target_family = "unix")]
 use convert_case::{Case, Casing};
+use minus_games_models::GameEngine;
 use minus_games_models::game_infos::GameInfos;
+use minus_games_utils::constants::SCUMMVM_SAVES;
 use std::env::consts::OS;
 use std::error::Error;
 #[cfg(target_family = "unix")]
 use std::os::unix::fs::PermissionsExt;
 #[cfg(target_family = "unix")]
 use std::path::Path;
+#[cfg(target_family = "unix")]
+use std::path::PathBuf;
 use std::process::Output;
 use std::sync::atomic::Ordering::Relaxed;
 use tokio::process::Command;
@@ -47,8 +51,12 @@ pub async fn run_game(game: &str) {
     get_config().mark_last_time_played(game);
 
     #[cfg(target_family = "unix")]
-    if infos.supports_linux() {
+    if let Some(emulator_args) = get_emulator_args(&infos) {
+        return run_game_with_emulator_on_linux(infos, emulator_args).await;
+    } else if infos.supports_linux() {
         return run_linux_game_on_linux(infos).await;
+    } else if matches!(infos.engine, GameEngine::RPGMakerRGSS) && get_config().mkxp_z.is_some() {
+        return run_rgss_game_with_mkxp_z(infos).await;
     } else if infos.supports_windows() {
         return run_windows_game_on_linux(infos).await;
     }
@@ -85,14 +93,18 @@ pub async fn run_windows_game_on_windows(infos: GameInfos) {
         .join(infos.folder_name.as_str())
         .join(infos.windows_exe.unwrap().as_str());
     let path_str = path.as_os_str().to_str().unwrap();
-    let cwd = get_config()
-        .get_game_path(infos.folder_name.as_str())
+    // The bundled emulator gets the configs of the game
+    let emulator_args = get_emulator_args(&infos).unwrap_or_default();
+    let cwd = infos
+        .get_emulator_working_folder(get_config().client_games_folder.as_path())
+        .unwrap_or_else(|| get_config().get_game_path(infos.folder_name.as_str()))
         .to_str()
         .unwrap()
         .to_string();
 
     let child = Command::new(path_str)
         .current_dir(&cwd)
+        .args(emulator_args)
         .spawn()
         .expect("Failed to spawn a child process!");
 
@@ -173,16 +185,147 @@ pub async fn run_windows_game_on_linux(infos: GameInfos) {
     }
 }
 
+/// mkxp-z runs the game scripts natively, it reads the Game.ini from the game folder.
 #[cfg(target_family = "unix")]
-fn has_gamemoderun() -> bool {
-    if let Some(path) = std::env::var_os("PATH") {
-        let paths = std::env::split_paths(&path);
-        for path in paths {
-            if path.join("gamemoderun").exists() {
-                debug!("Use gamemoderun");
-                return true;
+pub async fn run_rgss_game_with_mkxp_z(infos: GameInfos) {
+    let Some(mkxp_z) = get_config().mkxp_z.as_ref() else {
+        warn!("Cannot run the game since mkxp-z is not configured");
+        return;
+    };
+    send_event("Running game via mkxp-z on linux".into()).await;
+    let cwd = get_config().get_game_path(infos.folder_name.as_str());
+    let mut game_folder_arg = std::ffi::OsString::from("--gameFolder=");
+    game_folder_arg.push(cwd.as_os_str());
+    if get_config().verbose {
+        debug!("Running Cmd");
+        debug!(
+            r#"cd "{}" && "{}" --gameFolder="{}""#,
+            cwd.display(),
+            mkxp_z.display(),
+            cwd.display()
+        );
+    }
+
+    let child = match Command::new(mkxp_z)
+        .current_dir(&cwd)
+        .arg(game_folder_arg)
+        .spawn()
+    {
+        Ok(value) => value,
+        Err(err) => {
+            warn!("Failed to spawn a child process: {err}");
+            return;
+        }
+    };
+
+    CURRENT_GAME_PROCESS_ID.store(child.id().expect("Failed to get the process id"), Relaxed);
+    handle_command_output(child.wait_with_output().await, &infos.name);
+    CURRENT_GAME_PROCESS_ID.store(u32::MAX, Relaxed);
+}
+
+/// The arguments for DOSBox or ScummVM, the paths in them are absolute.
+fn get_emulator_args(infos: &GameInfos) -> Option<Vec<String>> {
+    let emulator_config = infos.emulator_config.as_ref()?;
+    let game_root =
+        std::path::absolute(get_config().get_game_path(infos.folder_name.as_str())).ok()?;
+    let config_files = emulator_config
+        .config_files
+        .iter()
+        .map(|config_file| game_root.join(config_file).to_str().map(str::to_string))
+        .collect::<Option<Vec<String>>>()?;
+    match infos.engine {
+        GameEngine::DOSBox => Some(
+            config_files
+                .into_iter()
+                .flat_map(|config_file| ["-conf".to_string(), config_file])
+                .collect(),
+        ),
+        GameEngine::ScummVM => {
+            let data_folder = infos
+                .get_emulator_working_folder(get_config().client_games_folder.as_path())
+                .unwrap_or_else(|| game_root.clone());
+            let save_folder = game_root.join(SCUMMVM_SAVES);
+            if let Err(err) = std::fs::create_dir_all(&save_folder) {
+                warn!("Failed to create the save folder: {err}");
+            }
+            let mut args: Vec<String> = config_files
+                .into_iter()
+                .map(|config_file| format!("--config={config_file}"))
+                .collect();
+            args.push(format!("--path={}", data_folder.to_str()?));
+            args.push(format!("--savepath={}", save_folder.to_str()?));
+            match emulator_config.target.as_ref() {
+                Some(target) => args.push(target.to_string()),
+                None => args.push("--auto-detect".to_string()),
             }
+            Some(args)
         }
+        _ => None,
+    }
+}
+
+#[cfg(target_family = "unix")]
+pub async fn run_game_with_emulator_on_linux(infos: GameInfos, emulator_args: Vec<String>) {
+    let (emulator, name) = match infos.engine {
+        GameEngine::ScummVM => (get_config().scummvm.clone(), "scummvm"),
+        _ => (get_config().dosbox.clone(), "dosbox"),
+    };
+    let Some(emulator) = emulator.or_else(|| find_in_path(name)) else {
+        if infos.supports_linux() {
+            warn!("{name} is not installed, running the linux version instead");
+            return run_linux_game_on_linux(infos).await;
+        }
+        if infos.supports_windows() {
+            warn!("{name} is not installed, running the windows version instead");
+            return run_windows_game_on_linux(infos).await;
+        }
+        warn!("Cannot run the game since {name} is not installed");
+        return;
+    };
+    send_event(format!("Running game via {name} on linux").into()).await;
+    let cwd = infos
+        .get_emulator_working_folder(get_config().client_games_folder.as_path())
+        .unwrap_or_else(|| get_config().get_game_path(infos.folder_name.as_str()));
+    if get_config().verbose {
+        debug!("Running Cmd");
+        debug!(
+            r#"cd "{}" && "{}" {}"#,
+            cwd.display(),
+            emulator.display(),
+            emulator_args.join(" ")
+        );
+    }
+
+    let child = match Command::new(emulator)
+        .current_dir(&cwd)
+        .args(emulator_args)
+        .spawn()
+    {
+        Ok(value) => value,
+        Err(err) => {
+            warn!("Failed to spawn a child process: {err}");
+            return;
+        }
+    };
+
+    CURRENT_GAME_PROCESS_ID.store(child.id().expect("Failed to get the process id"), Relaxed);
+    handle_command_output(child.wait_with_output().await, &infos.name);
+    CURRENT_GAME_PROCESS_ID.store(u32::MAX, Relaxed);
+}
+
+#[cfg(target_family = "unix")]
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|path| path.join(program))
+        .find(|path| path.exists())
+}
+
+#[cfg(target_family = "unix")]
+fn has_gamemoderun() -> bool {
+    if find_in_path("gamemoderun").is_some() {
+        debug!("Use gamemoderun");
+        return true;
     }
     debug!("gamemoderun not found");
     false
@@ -4,11 +4,14 @@ use tracing::info;
 pub fn scan_for_games() {
     let config = minus_games_finder::configuration::Configuration {
         games_folder: get_config().client_games_folder.clone(),
+        libraries: Vec::new(),
         data_folder: get_config().client_folder.clone(),
         cache_folder: get_config().client_cache_folder.clone(),
         cleanup_data_folder: false,
         keep_existing_configs: true,
         filter: None,
+        watch: false,
+        config_file: None,
     };
 
     info!("Run Finder");
@@ -1,5 +1,7 @@
 use crate::actions::delete::delete_game_info_files;
 use crate::actions::download::download_all_files;
+use crate::configuration::SyncBaseVersions;
+use crate::minus_games_client::SyncUploadStatus;
 use crate::runtime::{
     MinusGamesClientEvents, OFFLINE, STOP_DOWNLOAD, SYNC, SYNC_TESTED, get_client, get_config,
     get_installed_games, send_event,
@@ -7,12 +9,16 @@ use crate::runtime::{
 use crate::{offline_to_return, sync_to_return};
 use chrono::{DateTime, Utc};
 use minus_games_models::game_infos::GameInfos;
+use minus_games_models::sync_file_history::RestoreSyncFile;
 use minus_games_models::sync_file_info::SyncFileInfo;
-use minus_games_utils::{create_file_list, create_hash_from_string, set_file_modified_time};
+use minus_games_utils::{
+    create_file_list, create_hash_from_string, create_legacy_hash_from_string,
+    set_file_modified_time,
+};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering::{Relaxed, SeqCst};
-use std::time::SystemTime;
-use tracing::{debug, trace, warn};
+use tracing::{debug, info, trace, warn};
 
 pub async fn sync_infos_for_all_games() {
     let games = get_client().get_games_list().await.unwrap_or_default();
@@ -93,6 +99,7 @@ pub async fn download_sync_for_game(game: &str) {
         }
     };
 
+    let mut base_versions = get_config().get_sync_base_versions(game);
     if let Some(sync_folders) = &game_infos.sync_folders {
         for folder in sync_folders {
             let folder_hash = create_hash_from_string(folder);
@@ -101,29 +108,228 @@ pub async fn download_sync_for_game(game: &str) {
                 .await
             {
                 let sync_path: PathBuf = resolve_sync_path(folder, &game_infos);
+                let folder_base_versions =
+                    get_folder_base_versions(&mut base_versions, folder, &folder_hash);
                 for file_info in file_infos {
                     let download_file_path = sync_path.join(file_info.file_path.as_str());
 
-                    if !download_necessary(download_file_path.as_path(), file_info.last_modified) {
-                        continue;
+                    match get_sync_state(
+                        get_last_modified(download_file_path.as_path()),
+                        Some(file_info.last_modified),
+                        folder_base_versions.get(&file_info.file_path).copied(),
+                    ) {
+                        SyncState::InSync => {
+                            folder_base_versions
+                                .insert(file_info.file_path, file_info.last_modified);
+                            continue;
+                        }
+                        SyncState::LocalChanged => continue,
+                        SyncState::Conflict => {
+                            send_event(MinusGamesClientEvents::SaveConflict(
+                                game.to_string(),
+                                file_info.file_path,
+                            ))
+                            .await;
+                            continue;
+                        }
+                        SyncState::ServerChanged => {}
                     }
+
                     debug!("Downloading: {}", file_info);
-                    get_client()
+                    if !get_client()
                         .download_sync_file(
                             game,
                             &folder_hash,
                             file_info.file_path.as_str(),
                             download_file_path.as_path(),
                         )
-                        .await;
+                        .await
+                    {
+                        continue;
+                    }
                     set_file_modified_time(
                         download_file_path.as_path(),
                         file_info.last_modified.into(),
                     );
+                    folder_base_versions.insert(file_info.file_path, file_info.last_modified);
                 }
             }
         }
     }
+    get_config().save_sync_base_versions(game, &base_versions);
+}
+
+pub async fn resolve_save_conflict(game: &str, file_path: &str, keep_local: bool) {
+    offline_to_return!();
+    sync_to_return!();
+
+    let game_infos = match get_config().get_game_infos(game) {
+        Some(infos) => infos,
+        None => {
+            warn!("GameInfos not found for game {game}");
+            return;
+        }
+    };
+
+    let mut base_versions = get_config().get_sync_base_versions(game);
+    for folder in game_infos.sync_folders.iter().flatten() {
+        let folder_hash = create_hash_from_string(folder);
+        let server_last_modified = get_client()
+            .get_sync_file_list(game, &folder_hash)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .find(|sfi| sfi.file_path == file_path)
+            .map(|sfi| sfi.last_modified);
+        let local_last_modified = get_last_modified(
+            resolve_sync_path(folder, &game_infos)
+                .join(file_path)
+                .as_path(),
+        );
+
+        // Pretend the discarded side was the last synced version, so the normal sync overwrites it.
+        let base = if keep_local {
+            server_last_modified
+        } else {
+            local_last_modified
+        };
+        if let Some(base) = base {
+            get_folder_base_versions(&mut base_versions, folder, &folder_hash)
+                .insert(file_path.to_string(), base);
+            get_config().save_sync_base_versions(game, &base_versions);
+            if keep_local {
+                upload_sync_for_game(game).await;
+            } else {
+                download_sync_for_game(game).await;
+            }
+            return;
+        }
+    }
+
+    warn!("No save conflict found for {file_path}");
+}
+
+fn get_folder_base_versions<'a>(
+    base_versions: &'a mut SyncBaseVersions,
+    folder: &str,
+    folder_hash: &str,
+) -> &'a mut BTreeMap<String, DateTime<Utc>> {
+    if !base_versions.contains_key(folder_hash)
+        && let Some(legacy) = base_versions.remove(&create_legacy_hash_from_string(folder))
+    {
+        base_versions.insert(folder_hash.to_string(), legacy);
+    }
+    base_versions.entry(folder_hash.to_string()).or_default()
+}
+
+#[derive(Debug, PartialEq)]
+enum SyncState {
+    InSync,
+    LocalChanged,
+    ServerChanged,
+    Conflict,
+}
+
+fn get_sync_state(
+    local: Option<DateTime<Utc>>,
+    server: Option<DateTime<Utc>>,
+    base: Option<DateTime<Utc>>,
+) -> SyncState {
+    match (local, server) {
+        (None, None) => SyncState::InSync,
+        (Some(_), None) => SyncState::LocalChanged,
+        (None, Some(_)) => SyncState::ServerChanged,
+        (Some(local), Some(server)) if local == server => SyncState::InSync,
+        // Nothing synced yet since the base versions exist, the newest file wins
+        (Some(local), Some(server)) if base.is_none() => {
+            if local > server {
+                SyncState::LocalChanged
+            } else {
+                SyncState::ServerChanged
+            }
+        }
+        (Some(_), Some(server)) if base == Some(server) => SyncState::LocalChanged,
+        (Some(local), Some(_)) if base == Some(local) => SyncState::ServerChanged,
+        _ => SyncState::Conflict,
+    }
+}
+
+fn get_last_modified(path: &Path) -> Option<DateTime<Utc>> {
+    if !path.is_file() {
+        return None;
+    }
+    path.metadata()
+        .ok()?
+        .modified()
+        .ok()
+        .map(Into::<DateTime<Utc>>::into)
+}
+
+pub async fn list_save_history(game: &str) {
+    offline_to_return!();
+
+    let game_infos = match get_config().get_game_infos(game) {
+        Some(infos) => infos,
+        None => {
+            warn!("GameInfos not found for game {game}");
+            return;
+        }
+    };
+
+    for folder in game_infos.sync_folders.iter().flatten() {
+        let folder_hash = create_hash_from_string(folder);
+        let histories = get_client()
+            .get_sync_file_history(game, &folder_hash)
+            .await
+            .unwrap_or_default();
+        for history in histories {
+            info!("{folder}/{}", history.file_path);
+            for revision in history.revisions {
+                info!("    {revision}");
+            }
+        }
+    }
+}
+
+pub async fn restore_save(game: &str, file_path: &str, revision: &str) {
+    offline_to_return!();
+
+    let game_infos = match get_config().get_game_infos(game) {
+        Some(infos) => infos,
+        None => {
+            warn!("GameInfos not found for game {game}");
+            return;
+        }
+    };
+
+    for folder in game_infos.sync_folders.iter().flatten() {
+        let folder_hash = create_hash_from_string(folder);
+        let Some(histories) = get_client().get_sync_file_history(game, &folder_hash).await else {
+            continue;
+        };
+        let found = histories.iter().any(|history| {
+            history.file_path == file_path
+                && history.revisions.iter().any(|r| r.revision == revision)
+        });
+        if !found {
+            continue;
+        }
+
+        let restore = RestoreSyncFile {
+            file_path: file_path.to_string(),
+            revision: revision.to_string(),
+        };
+        if get_client()
+            .restore_sync_file(game, &folder_hash, &restore)
+            .await
+        {
+            info!("Restored {file_path} to revision {revision}");
+            download_sync_for_game(game).await;
+        }
+        return;
+    }
+
+    warn!("Revision {revision} not found for {file_path}");
 }
 
 #[inline]
@@ -132,11 +338,6 @@ fn check_if_is_wine(game_infos: &GameInfos) -> bool {
     !game_infos.supports_linux()
 }
 
-fn download_necessary(path: &Path, last_modified: DateTime<Utc>) -> bool {
-    !path.is_file()
-        || path.metadata().unwrap().modified().unwrap() != Into::<SystemTime>::into(last_modified)
-}
-
 pub async fn upload_sync_for_game(game: &str) {
     offline_to_return!();
     sync_to_return!();
@@ -150,52 +351,82 @@ pub async fn upload_sync_for_game(game: &str) {
         }
     };
 
+    let mut base_versions = get_config().get_sync_base_versions(game);
     if let Some(sync_folders) = &game_infos.sync_folders {
         for folder in sync_folders {
             let folder_hash = create_hash_from_string(folder);
             let sync_path: PathBuf = resolve_sync_path(folder, &game_infos);
-            let sfi_server = get_client().get_sync_file_list(game, &folder_hash).await;
+            let sfi_server = get_client()
+                .get_sync_file_list(game, &folder_hash)
+                .await
+                .unwrap_or_default();
+            let folder_base_versions =
+                get_folder_base_versions(&mut base_versions, folder, &folder_hash);
             let file_list = create_file_list(&sync_path);
             let absolute_path = std::path::absolute(sync_path).unwrap();
             let cut_off = absolute_path.iter().count();
             trace!("Syncing: {} - {}", folder, absolute_path.display());
             for file in file_list {
                 let sfi = SyncFileInfo::from_path_with_cut_off(file, cut_off);
-                if game_infos.is_excluded(sfi.file_path.as_str()) {
+                if game_infos.is_excluded(sfi.file_path.as_str())
+                    || !game_infos.is_included(sfi.file_path.as_str())
+                {
                     continue;
                 }
 
                 let upload_file_path = absolute_path.join(sfi.file_path.as_str());
-
-                if !needs_upload(&sfi, &sfi_server) {
-                    continue;
+                let server_last_modified = sfi_server
+                    .iter()
+                    .find(|ssfi| sfi.file_path.as_str() == ssfi.file_path.as_str())
+                    .map(|ssfi| ssfi.last_modified);
+                let base = folder_base_versions.get(&sfi.file_path).copied();
+
+                match get_sync_state(Some(sfi.last_modified), server_last_modified, base) {
+                    SyncState::InSync => {
+                        folder_base_versions.insert(sfi.file_path, sfi.last_modified);
+                        continue;
+                    }
+                    SyncState::ServerChanged => continue,
+                    SyncState::Conflict => {
+                        send_event(MinusGamesClientEvents::SaveConflict(
+                            game.to_string(),
+                            sfi.file_path,
+                        ))
+                        .await;
+                        continue;
+                    }
+                    SyncState::LocalChanged => {}
                 }
 
                 debug!("Uploading: {}", sfi);
-                get_client()
-                    .upload_sync_file(game, folder_hash.as_str(), sfi, upload_file_path)
-                    .await;
+                let file_path = sfi.file_path.clone();
+                let last_modified = sfi.last_modified;
+                match get_client()
+                    .upload_sync_file(
+                        game,
+                        folder_hash.as_str(),
+                        sfi,
+                        upload_file_path,
+                        server_last_modified,
+                    )
+                    .await
+                {
+                    SyncUploadStatus::Uploaded => {
+                        folder_base_versions.insert(file_path, last_modified);
+                    }
+                    SyncUploadStatus::Conflict => {
+                        send_event(MinusGamesClientEvents::SaveConflict(
+                            game.to_string(),
+                            file_path,
+                        ))
+                        .await;
+                    }
+                    SyncUploadStatus::Failed => {}
+                }
             }
         }
     }
-}
-
-fn needs_upload(sfi: &SyncFileInfo, sfi_server_list: &Option<Vec<SyncFileInfo>>) -> bool {
-    if sfi_server_list.is_none() {
-        return true;
-    }
-
-    let list_ref = sfi_server_list.as_ref().unwrap();
-
-    let item = list_ref
-        .iter()
-        .find(|ssfi| sfi.file_path.as_str() == ssfi.file_path.as_str());
-
-    if item.is_none() {
-        return true;
-    }
-
-    item.unwrap().last_modified != sfi.last_modified
+    get_config().save_sync_base_versions(game, &base_versions);
 }
 
 fn resolve_sync_path(to_resolve: &str, game_infos: &GameInfos) -> PathBuf {
@@ -228,6 +459,16 @@ fn resolve_sync_path(to_resolve: &str, game_infos: &GameInfos) -> PathBuf {
                     rtn.push(value);
                 }
             }
+            "$GODOT_USER_DATA" => {
+                if let Some(value) = resolve_godot_user_data_path(game_infos) {
+                    rtn.push(value);
+                }
+            }
+            "$LOCAL_APPDATA_OR_CONFIG" => {
+                if let Some(value) = get_local_appdata_or_config(game_infos) {
+                    rtn.push(value);
+                }
+            }
             #[cfg(target_family = "windows")]
             "$ELECTRON_CONFIG" | "$APPDATA_ROAMING" | "APPDATA_ROAMING_OR_CONFIG" => {
                 if let Some(value) = get_appdata_roaming() {
@@ -314,6 +555,47 @@ fn get_config_path() -> Option<PathBuf> {
     }
 }
 
+#[cfg(target_family = "windows")]
+fn get_local_appdata_or_config(_: &GameInfos) -> Option<PathBuf> {
+    let local_appdata = std::env::var("LOCALAPPDATA").ok()?;
+    Some(PathBuf::from(local_appdata))
+}
+
+#[cfg(not(target_family = "windows"))]
+fn get_local_appdata_or_config(game_infos: &GameInfos) -> Option<PathBuf> {
+    if check_if_is_wine(game_infos) {
+        let wine_prefix = get_config().wine_prefix.as_ref()?;
+        let user = get_user()?;
+        let rtn = wine_prefix
+            .join("pfx")
+            .join("drive_c")
+            .join("users")
+            .join(user)
+            .join("AppData")
+            .join("Local");
+        Some(rtn)
+    } else {
+        get_config_path()
+    }
+}
+
+#[cfg(target_family = "windows")]
+fn resolve_godot_user_data_path(_: &GameInfos) -> Option<PathBuf> {
+    Some(get_appdata_roaming()?.join("Godot").join("app_userdata"))
+}
+
+#[cfg(not(target_family = "windows"))]
+fn resolve_godot_user_data_path(game_infos: &GameInfos) -> Option<PathBuf> {
+    if check_if_is_wine(game_infos) {
+        return Some(
+            get_appdata_roaming(game_infos)?
+                .join("Godot")
+                .join("app_userdata"),
+        );
+    }
+    Some(dirs::data_dir()?.join("godot").join("app_userdata"))
+}
+
 #[cfg(target_family = "windows")]
 fn get_local_low() -> Option<PathBuf> {
     let local_appdata = std::env::var("LOCALAPPDATA").ok()?;
@@ -420,3 +702,97 @@ pub fn get_user() -> Option<String> {
     }
     std::env::var("USER").ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::actions::sync::{SyncState, get_sync_state};
+    use chrono::{DateTime, Duration, Utc};
+
+    fn times() -> (DateTime<Utc>, DateTime<Utc>, DateTime<Utc>) {
+        let old = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        (old, old + Duration::hours(1), old + Duration::hours(2))
+    }
+
+    #[test]
+    fn test_sync_state_one_side_only() {
+        let (old, _, _) = times();
+        assert_eq!(get_sync_state(None, None, None), SyncState::InSync);
+        assert_eq!(
+            get_sync_state(Some(old), None, None),
+            SyncState::LocalChanged
+        );
+        assert_eq!(
+            get_sync_state(None, Some(old), None),
+            SyncState::ServerChanged
+        );
+        assert_eq!(
+            get_sync_state(Some(old), None, Some(old)),
+            SyncState::LocalChanged
+        );
+        assert_eq!(
+            get_sync_state(None, Some(old), Some(old)),
+            SyncState::ServerChanged
+        );
+    }
+
+    #[test]
+    fn test_sync_state_equal_timestamps() {
+        let (old, middle, _) = times();
+        assert_eq!(
+            get_sync_state(Some(old), Some(old), None),
+            SyncState::InSync
+        );
+        assert_eq!(
+            get_sync_state(Some(old), Some(old), Some(middle)),
+            SyncState::InSync
+        );
+    }
+
+    #[test]
+    fn test_sync_state_one_side_changed_since_base() {
+        let (old, middle, _) = times();
+        assert_eq!(
+            get_sync_state(Some(middle), Some(old), Some(old)),
+            SyncState::LocalChanged
+        );
+        assert_eq!(
+            get_sync_state(Some(old), Some(middle), Some(old)),
+            SyncState::ServerChanged
+        );
+        // Only the base decides, not which side is newer
+        assert_eq!(
+            get_sync_state(Some(old), Some(middle), Some(middle)),
+            SyncState::LocalChanged
+        );
+        assert_eq!(
+            get_sync_state(Some(middle), Some(old), Some(middle)),
+            SyncState::ServerChanged
+        );
+    }
+
+    #[test]
+    fn test_sync_state_both_changed() {
+        let (old, middle, new) = times();
+        assert_eq!(
+            get_sync_state(Some(middle), Some(new), Some(old)),
+            SyncState::Conflict
+        );
+        assert_eq!(
+            get_sync_state(Some(new), Some(middle), Some(old)),
+            SyncState::Conflict
+        );
+    }
+
+    #[test]
+    fn test_sync_state_without_base_newest_wins() {
+        let (old, middle, _) = times();
+        assert_eq!(
+            get_sync_state(Some(middle), Some(old), None),
+            SyncState::LocalChanged
+        );
+        assert_eq!(
+            get_sync_state(Some(old), Some(middle), None),
+            SyncState::ServerChanged
+        );
+    }
+}
@@ -1,19 +1,24 @@
 use crate::utils::{is_or_none, is_or_none_path_buf, is_or_none_string};
+use chrono::{DateTime, Utc};
 use clap::{Parser, Subcommand};
 use log::warn;
 use minus_games_models::game_file_info::GameFileInfo;
 use minus_games_models::game_infos::GameInfos;
 use minus_games_utils::constants::{ADDITIONS, INFOS};
-use minus_games_utils::{ClientFolder, get_last_time_played_path};
+use minus_games_utils::{ClientFolder, get_last_time_played_path, get_sync_base_versions_path};
 use minus_games_utils::{ClientGamesFolder, get_csv_path, get_dirty_path, get_game_infos_path};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fmt::{Display, Formatter};
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, BufWriter};
 use std::path::PathBuf;
 use std::time::SystemTime;
 
+/// Server side `last_modified` of every sync file at the time it was last synced, per folder hash.
+pub type SyncBaseVersions = BTreeMap<String, BTreeMap<String, DateTime<Utc>>>;
+
 #[derive(Debug, clap::Args, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct DownloadArgs {
     #[arg()]
@@ -50,6 +55,10 @@ pub enum ClientActions {
     },
     CheckForCorruption {
         game: String,
+        #[arg(long)]
+        fix: bool,
+        #[arg(long)]
+        json: bool,
     },
     SelectRepair,
     DownloadSyncs,
@@ -57,12 +66,43 @@ pub enum ClientActions {
         game: String,
     },
     UploadSyncs,
+    ListSaveHistory {
+        game: String,
+    },
+    RestoreSave {
+        game: String,
+        file_path: String,
+        revision: String,
+    },
+    ResolveSaveConflict {
+        game: String,
+        file_path: String,
+        #[arg(long)]
+        keep_local: bool,
+    },
+    Login {
+        #[arg(long)]
+        device_name: Option<String>,
+    },
+    ListTokens,
+    RevokeToken {
+        id: String,
+    },
     ScanForGames,
     #[cfg(target_family = "unix")]
     SelectGameToPlay,
     Gui,
 }
 
+impl ClientActions {
+    pub fn is_json_output(&self) -> bool {
+        matches!(
+            self,
+            ClientActions::ListJson | ClientActions::CheckForCorruption { json: true, .. }
+        )
+    }
+}
+
 #[derive(Parser, Debug, Serialize, Deserialize, Clone)]
 #[command(author, version, about, long_about = None)]
 pub struct ClientConfiguration {
@@ -78,6 +118,15 @@ pub struct ClientConfiguration {
     pub wine_exe: Option<PathBuf>,
     #[arg(long, env = "MINUS_GAMES_WINE_PREFIX")]
     pub wine_prefix: Option<PathBuf>,
+    /// Runs RPG Maker XP, VX and VX Ace games on Linux instead of Wine
+    #[arg(long, env = "MINUS_GAMES_MKXP_Z")]
+    pub mkxp_z: Option<PathBuf>,
+    /// Runs DOSBox games on Linux, by default dosbox from the PATH
+    #[arg(long, env = "MINUS_GAMES_DOSBOX")]
+    pub dosbox: Option<PathBuf>,
+    /// Runs ScummVM games on Linux, by default scummvm from the PATH
+    #[arg(long, env = "MINUS_GAMES_SCUMMVM")]
+    pub scummvm: Option<PathBuf>,
     #[arg(short, long, default_value = "false", env = "MINUS_GAMES_VERBOSE")]
     pub verbose: bool,
     #[arg(short, long, default_value = "false", env = "MINUS_GAMES_OFFLINE")]
@@ -90,10 +139,20 @@ pub struct ClientConfiguration {
     pub username: Option<String>,
     #[arg(long, env = "MINUS_GAMES_PASSWORD")]
     pub password: Option<String>,
+    #[arg(long, env = "MINUS_GAMES_TOKEN")]
+    pub token: Option<String>,
+    /// PEM file with additional trusted certificates
+    #[arg(long, env = "MINUS_GAMES_CA_BUNDLE")]
+    pub ca_bundle: Option<PathBuf>,
+    /// Sha256 fingerprint of a self-signed server certificate
+    #[arg(long, env = "MINUS_GAMES_CERT_FINGERPRINT")]
+    pub cert_fingerprint: Option<String>,
     #[arg(long, default_value = "false", env = "MINUS_GAMES_NO_GAMEMODERUN")]
     pub no_gamemoderun: bool,
     #[arg(long, default_value = "true", env = "MINUS_GAMES_SYNC")]
     pub sync: bool,
+    #[arg(long, default_value = "3", env = "MINUS_GAMES_DOWNLOAD_RETRIES")]
+    pub download_retries: usize,
     #[command(subcommand)]
     pub action: Option<ClientActions>,
 }
@@ -140,6 +199,33 @@ impl ClientConfiguration {
         get_last_time_played_path(&self.client_folder, game)
     }
 
+    pub fn get_sync_base_versions_path_for_game(&self, game: &str) -> PathBuf {
+        get_sync_base_versions_path(&self.client_folder, game)
+    }
+
+    pub fn get_sync_base_versions(&self, game: &str) -> SyncBaseVersions {
+        let path = self.get_sync_base_versions_path_for_game(game);
+        let Ok(file) = File::open(path) else {
+            return SyncBaseVersions::new();
+        };
+        serde_json::from_reader(BufReader::new(file)).unwrap_or_default()
+    }
+
+    pub fn save_sync_base_versions(&self, game: &str, base_versions: &SyncBaseVersions) {
+        let path = self.get_sync_base_versions_path_for_game(game);
+        let result = match File::create(path.as_path()) {
+            Ok(file) => serde_json::to_writer(BufWriter::new(file), base_versions)
+                .map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        };
+        if let Err(err) = result {
+            warn!(
+                "Could not save sync base versions! - Game: {game} - Path {} - {err}",
+                path.display()
+            );
+        }
+    }
+
     pub fn get_game_last_action_date(&self, game: &str) -> SystemTime {
         let game_last_played_path = self.get_last_time_played_path_for_game(game);
         if game_last_played_path.is_file() {
@@ -311,9 +397,20 @@ impl Display for ClientConfiguration {
         }
         writeln!(f, "Wine Exe: {}", is_or_none_path_buf(&self.wine_exe))?;
         writeln!(f, "Wine Prefix: {}", is_or_none_path_buf(&self.wine_prefix))?;
+        writeln!(f, "mkxp-z: {}", is_or_none_path_buf(&self.mkxp_z))?;
+        writeln!(f, "DOSBox: {}", is_or_none_path_buf(&self.dosbox))?;
+        writeln!(f, "ScummVM: {}", is_or_none_path_buf(&self.scummvm))?;
         writeln!(f, "Username: {}", is_or_none_string(&self.username))?;
+        writeln!(f, "Token: {}", self.token.is_some())?;
+        writeln!(f, "CA Bundle: {}", is_or_none_path_buf(&self.ca_bundle))?;
+        writeln!(
+            f,
+            "Cert Fingerprint: {}",
+            is_or_none_string(&self.cert_fingerprint)
+        )?;
         writeln!(f, "Offline: {:?}", self.offline)?;
         writeln!(f, "Sync: {:?}", self.sync)?;
+        writeln!(f, "Download Retries: {}", self.download_retries)?;
         write!(f, "Action: {}", is_or_none(self.action.as_ref()))
     }
 }
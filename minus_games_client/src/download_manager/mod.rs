@@ -1,7 +1,9 @@
-use crate::runtime::{MinusGamesClientEvents, STOP_DOWNLOAD, get_client, send_event};
+use crate::runtime::{MinusGamesClientEvents, STOP_DOWNLOAD, get_client, get_config, send_event};
+use crate::utils::get_blake3_hash;
 use chrono::DateTime;
+use minus_games_models::game_file_info::GameFileInfo;
 use minus_games_utils::set_file_modified_time;
-use reqwest::Response;
+use reqwest::{Response, StatusCode};
 use std::io::Write;
 use std::num::NonZero;
 use std::path::{Path, PathBuf};
@@ -12,10 +14,14 @@ use tokio::sync::Semaphore;
 use tokio::task::JoinHandle;
 use tracing::{trace, warn};
 
+pub(crate) const PART_EXTENSION: &str = ".part";
+
 pub struct DownloadConfig {
     pub url: String,
     pub to: String,
     pub to_final: Option<PathBuf>,
+    pub size: Option<u64>,
+    pub hash: Option<String>,
 }
 
 impl DownloadConfig {
@@ -24,6 +30,18 @@ impl DownloadConfig {
             url,
             to,
             to_final: None,
+            size: None,
+            hash: None,
+        }
+    }
+
+    pub fn from_game_file_info(game_file_info: GameFileInfo, base_url: &str) -> Self {
+        Self {
+            url: game_file_info.generate_download_link(base_url),
+            to: game_file_info.file_path,
+            to_final: None,
+            size: Some(game_file_info.size),
+            hash: Some(game_file_info.hash),
         }
     }
 }
@@ -41,6 +59,7 @@ impl DownloadManager {
             .unwrap_or(NonZero::new(1).unwrap())
             .get();
         let processes: usize = (parallelism / 2).max(1);
+        let retries = get_config().download_retries;
         let semaphore = Arc::new(Semaphore::new(processes));
         let mut joinings: Vec<JoinHandle<()>> = Vec::new();
         send_event(MinusGamesClientEvents::StartDownloadingFiles(
@@ -59,7 +78,10 @@ impl DownloadManager {
                     processes - pass.semaphore().available_permits()
                 );
                 send_event(MinusGamesClientEvents::StartDownloadingFile).await;
-                download_to(config).await;
+                if !download_with_retries(&config, retries).await && !STOP_DOWNLOAD.load(Relaxed) {
+                    warn!("Failed to download: {}", config.to);
+                    send_event(MinusGamesClientEvents::FailedDownloadingFile(config.to)).await;
+                }
                 send_event(MinusGamesClientEvents::FinishedDownloadingFile).await;
                 drop(pass);
             });
@@ -75,28 +97,158 @@ impl DownloadManager {
     }
 }
 
-pub async fn download_to(download_config: DownloadConfig) {
-    let to = download_config.to_final.unwrap();
+async fn download_with_retries(download_config: &DownloadConfig, retries: usize) -> bool {
+    for attempt in 0..=retries {
+        if STOP_DOWNLOAD.load(Relaxed) {
+            return false;
+        }
+        if attempt != 0 {
+            warn!(
+                "Retry download of {} ({attempt}/{retries})",
+                download_config.to
+            );
+        }
+        if download_to(download_config).await {
+            return true;
+        }
+    }
+    false
+}
+
+pub async fn download_to(download_config: &DownloadConfig) -> bool {
+    let to = download_config.to_final.as_ref().unwrap();
     if to.exists() {
-        return;
+        return true;
+    }
+
+    let part = get_part_path(to.as_path());
+    let mut resume_from = part.metadata().map(|m| m.len()).unwrap_or(0);
+
+    if let Some(size) = download_config.size
+        && resume_from > size
+    {
+        warn!(
+            "Partial download is bigger than expected. Restart download of: {}",
+            to.display()
+        );
+        std::fs::remove_file(part.as_path()).ok();
+        resume_from = 0;
+    }
+
+    if download_config.size.is_none_or(|size| resume_from < size) {
+        let response = if resume_from == 0 {
+            get_client().get(&download_config.url).await
+        } else {
+            trace!("Resume download of {} at {resume_from}", to.display());
+            get_client()
+                .get_from_offset(&download_config.url, resume_from)
+                .await
+        };
+        let response = match response {
+            Ok(response) => response,
+            Err(err) => {
+                warn!("Download {} failed with: {err}", download_config.url);
+                return false;
+            }
+        };
+
+        let status = response.status();
+        if status == StatusCode::RANGE_NOT_SATISFIABLE && resume_from != 0 {
+            trace!("Partial download is already complete: {}", part.display());
+        } else if !status.is_success() {
+            warn!(
+                "Download {} failed with: {} - {}",
+                download_config.url,
+                status,
+                response.text().await.unwrap_or_default()
+            );
+            return false;
+        } else {
+            let append = status == StatusCode::PARTIAL_CONTENT;
+            if !write_response_to(response, part.as_path(), append).await
+                || STOP_DOWNLOAD.load(Relaxed)
+            {
+                return false;
+            }
+        }
+    }
+
+    finish_partial_download(
+        part.as_path(),
+        to.as_path(),
+        download_config.size,
+        download_config.hash.clone(),
+    )
+    .await
+}
+
+async fn finish_partial_download(
+    part: &Path,
+    to: &Path,
+    size: Option<u64>,
+    hash: Option<String>,
+) -> bool {
+    let part_size = match part.metadata() {
+        Ok(metadata) => metadata.len(),
+        Err(err) => {
+            warn!("Partial download not found: {} - {err}", part.display());
+            return false;
+        }
+    };
+
+    if let Some(size) = size
+        && part_size != size
+    {
+        warn!(
+            "Download of {} is incomplete. Expected size: {size} - Actual size: {part_size}",
+            to.display()
+        );
+        if part_size > size {
+            std::fs::remove_file(part).ok();
+        }
+        return false;
     }
 
-    let response = get_client().get(&download_config.url).await;
+    if let Some(hash) = hash {
+        let part_path = part.to_path_buf();
+        let part_hash = tokio::task::spawn_blocking(move || get_blake3_hash(part_path.as_path()))
+            .await
+            .ok()
+            .flatten();
+        if part_hash.as_ref() != Some(&hash) {
+            warn!(
+                "Download of {} is corrupt. Expected hash: {hash} - Actual hash: {}",
+                to.display(),
+                part_hash.unwrap_or_default()
+            );
+            std::fs::remove_file(part).ok();
+            return false;
+        }
+    }
 
-    if !response.status().is_success() {
+    if let Err(err) = std::fs::rename(part, to) {
         warn!(
-            "Download {} failed with: {} - {}",
-            download_config.url,
-            response.status(),
-            response.text().await.unwrap()
+            "Failed to move {} to {} - Err: {err}",
+            part.display(),
+            to.display()
         );
-        return;
+        return false;
     }
 
-    download_loop(response, to.as_path()).await;
+    true
+}
+
+pub fn get_part_path(to: &Path) -> PathBuf {
+    let mut part_name = to.file_name().unwrap_or_default().to_os_string();
+    part_name.push(PART_EXTENSION);
+    to.with_file_name(part_name)
+}
+
+pub async fn download_loop(response: Response, to: &Path) -> bool {
+    write_response_to(response, to, false).await
 }
 
-pub async fn download_loop(mut response: Response, to: &Path) {
+async fn write_response_to(mut response: Response, to: &Path, append: bool) -> bool {
     trace!("Download From: {} - To: {}", response.url(), to.display());
 
     let parent = to.parent().unwrap();
@@ -111,11 +263,17 @@ pub async fn download_loop(mut response: Response, to: &Path) {
         }
     }
 
-    let download_file = match std::fs::File::create(to) {
+    let download_file = match std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(to)
+    {
         Ok(file) => file,
         Err(err) => {
             warn!("File could not be created {err} - File: {}", to.display());
-            return;
+            return false;
         }
     };
 
@@ -123,18 +281,27 @@ pub async fn download_loop(mut response: Response, to: &Path) {
 
     // let header = response.headers().get("last-modified").cloned();
 
+    let mut success = true;
     loop {
         if STOP_DOWNLOAD.load(Relaxed) {
             break;
             // return;
         }
 
-        if let Some(bytes) = response.chunk().await.unwrap() {
-            if let Err(err) = writer.write(&bytes) {
-                warn!("Download failed with: {err}");
+        match response.chunk().await {
+            Ok(Some(bytes)) => {
+                if let Err(err) = writer.write_all(&bytes) {
+                    warn!("Download failed with: {err}");
+                    success = false;
+                    break;
+                }
+            }
+            Ok(None) => break,
+            Err(err) => {
+                warn!("Download of {} was interrupted: {err}", to.display());
+                success = false;
+                break;
             }
-        } else {
-            break;
         };
     }
 
@@ -145,7 +312,10 @@ pub async fn download_loop(mut response: Response, to: &Path) {
     // tokio::io::copy_buf(&mut reader, &mut writer).await.unwrap();
 
     // download_file.flush().ok();
-    writer.flush().ok();
+    if let Err(err) = writer.flush() {
+        warn!("Failed to write {} - Err: {err}", to.display());
+        success = false;
+    }
     std::mem::drop(writer);
 
     // if let Some(last_modified_header_value) = header
@@ -155,4 +325,6 @@ pub async fn download_loop(mut response: Response, to: &Path) {
     {
         set_file_modified_time(to, last_modified.into());
     }
+
+    success
 }
@@ -1,3 +1,4 @@
+use crate::actions::auth::{list_tokens, login, revoke_token};
 use crate::actions::delete::delete_game;
 use crate::actions::download::download;
 #[cfg(target_family = "unix")]
@@ -6,15 +7,17 @@ use crate::actions::menu::{
     select_download, select_game, select_game_to_delete, select_repair, start_menu,
 };
 use crate::actions::other::{list, list_json};
-use crate::actions::repair::repair_game;
+use crate::actions::repair::{check_for_corruption_for_game, repair_game};
 use crate::actions::run::{run_game, run_game_synced, sync_run_game};
 use crate::actions::scan::scan_for_games;
 use crate::actions::sync::{
-    download_sync_for_game, download_syncs, sync_infos_for_all_games, upload_syncs,
+    download_sync_for_game, download_syncs, list_save_history, resolve_save_conflict, restore_save,
+    sync_infos_for_all_games, upload_syncs,
 };
 use crate::configuration::ClientActions;
 use crate::runtime::{MinusGamesClientEvents, OFFLINE, SYNC, get_config, send_event, set_sender};
 use indicatif::ProgressBar;
+use std::process::ExitCode;
 use std::sync::atomic::Ordering;
 use tracing::{debug, info, warn};
 
@@ -25,7 +28,7 @@ pub mod minus_games_client;
 pub mod runtime;
 pub mod utils;
 
-pub async fn run_cli() {
+pub async fn run_cli() -> ExitCode {
     let action = get_config()
         .action
         .as_ref()
@@ -52,12 +55,20 @@ pub async fn run_cli() {
                         bar.inc(1);
                     }
                 }
+                MinusGamesClientEvents::FailedDownloadingFile(file) => {
+                    warn!("Download failed permanently: {file}");
+                }
                 MinusGamesClientEvents::FinishedDownloadingFiles => {
                     if let Some(bar) = &bar_option {
                         bar.finish();
                     }
                     bar_option = None;
                 }
+                MinusGamesClientEvents::SaveConflict(game, file_path) => {
+                    warn!(
+                        "Save conflict for {game}: {file_path} - use resolve-save-conflict to choose which copy to keep"
+                    );
+                }
                 MinusGamesClientEvents::LogInfoMessage(msg) => {
                     info!("{msg}");
                 }
@@ -75,6 +86,7 @@ pub async fn run_cli() {
     });
 
     // Main
+    let mut exit_code = ExitCode::SUCCESS;
     match action {
         ClientActions::List => {
             list().await;
@@ -95,11 +107,27 @@ pub async fn run_cli() {
         ClientActions::SelectDeleteGame { purge } => select_game_to_delete(purge.unwrap_or(true)),
         ClientActions::Menu => start_menu().await,
         ClientActions::Repair { game } => repair_game(&game).await,
-        ClientActions::CheckForCorruption { game } => repair_game(&game).await,
+        ClientActions::CheckForCorruption { game, fix, json } => {
+            exit_code = check_for_corruption_for_game(&game, fix, json).await;
+        }
         ClientActions::SelectRepair => select_repair().await,
         ClientActions::DownloadSyncs => download_syncs().await,
         ClientActions::DownloadSync { game } => download_sync_for_game(&game).await,
         ClientActions::UploadSyncs => upload_syncs().await,
+        ClientActions::ListSaveHistory { game } => list_save_history(&game).await,
+        ClientActions::RestoreSave {
+            game,
+            file_path,
+            revision,
+        } => restore_save(&game, &file_path, &revision).await,
+        ClientActions::ResolveSaveConflict {
+            game,
+            file_path,
+            keep_local,
+        } => resolve_save_conflict(&game, &file_path, keep_local).await,
+        ClientActions::Login { device_name } => login(device_name.as_ref()).await,
+        ClientActions::ListTokens => list_tokens().await,
+        ClientActions::RevokeToken { id } => revoke_token(&id).await,
         ClientActions::ScanForGames => scan_for_games(),
         #[cfg(target_family = "unix")]
         ClientActions::SelectGameToPlay => select_game_to_play().await,
@@ -111,4 +139,5 @@ pub async fn run_cli() {
     // Cleanup
     send_event(MinusGamesClientEvents::Close).await;
     event_handle.await.unwrap();
+    exit_code
 }
@@ -1,9 +1,9 @@
-use minus_games_client::configuration::ClientActions::ListJson;
 use minus_games_client::run_cli;
 use minus_games_client::runtime::get_config;
 use std::process::ExitCode;
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
 #[tokio::main]
 async fn main() -> ExitCode {
@@ -25,9 +25,22 @@ async fn main() -> ExitCode {
         EnvFilter::default().add_directive(LevelFilter::INFO.into())
         // .add_directive("minus_games_client=debug".parse().unwrap())
     };
-    tracing_subscriber::fmt().with_env_filter(filter).init();
+    let json_output = get_config()
+        .action
+        .as_ref()
+        .is_some_and(|a| a.is_json_output());
+    // Keeps stdout parseable, when the output is JSON
+    let writer = if json_output {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .init();
 
-    if !get_config().action.as_ref().is_some_and(|a| a == &ListJson) {
+    if !json_output {
         println!("Minus Games Version {}", env!("CARGO_PKG_VERSION"));
         println!("Config:");
         println!("{}", get_config());
@@ -38,6 +51,5 @@ async fn main() -> ExitCode {
         return ExitCode::FAILURE;
     }
 
-    run_cli().await;
-    ExitCode::SUCCESS
+    run_cli().await
 }
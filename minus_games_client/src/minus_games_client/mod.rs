@@ -6,28 +6,44 @@ use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
 use chrono::{DateTime, Utc};
 use log::{debug, warn};
+use minus_games_models::auth_token::{AuthToken, AuthTokenInfo, CreateAuthToken};
 use minus_games_models::game_list::{GamesWithInfos, GamesWithMinimalGameInfos};
+use minus_games_models::sync_file_history::{RestoreSyncFile, SyncFileHistory};
 use minus_games_models::sync_file_info::SyncFileInfo;
+use minus_games_utils::tls::configure_tls;
 use minus_games_utils::{create_game_infos_name, get_csv_name};
-use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue, IF_MODIFIED_SINCE};
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue, IF_MODIFIED_SINCE, RANGE};
 use reqwest::{Body, Client, Response, StatusCode, Url, multipart};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering::Relaxed;
 use std::time::SystemTime;
 use tokio_util::codec::{BytesCodec, FramedRead};
 
+pub enum SyncUploadStatus {
+    Uploaded,
+    Conflict,
+    Failed,
+}
+
 pub struct MinusGamesClient {
     client: Client,
     url: Url,
 }
 
 impl MinusGamesClient {
-    pub async fn get(&self, url: &str) -> Response {
+    pub async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
+        self.client.get(url).send().await
+    }
+    pub async fn get_from_offset(
+        &self,
+        url: &str,
+        offset: u64,
+    ) -> Result<Response, reqwest::Error> {
         self.client
             .get(url)
+            .header(RANGE, format!("bytes={offset}-"))
             .send()
             .await
-            .unwrap_or_else(|err| panic!("Error getting url from {url} with err: {err}"))
     }
     pub async fn can_sync(&self) -> bool {
         let url = self.url.join("/sync").unwrap();
@@ -58,7 +74,8 @@ impl MinusGamesClient {
         folder_hash: &str,
         sync_file_info: SyncFileInfo,
         upload_file_path: PathBuf,
-    ) {
+        base_last_modified: Option<DateTime<Utc>>,
+    ) -> SyncUploadStatus {
         debug!("Uploading: {}", upload_file_path.display());
         let url = self
             .url
@@ -71,7 +88,7 @@ impl MinusGamesClient {
             Ok(to_upload) => to_upload,
             Err(err) => {
                 warn!("Failed Uploading Sync Files with: {err}");
-                return;
+                return SyncUploadStatus::Failed;
             }
         };
         let stream = FramedRead::new(to_upload, BytesCodec::new());
@@ -81,27 +98,37 @@ impl MinusGamesClient {
             .mime_str(mime::APPLICATION_OCTET_STREAM.as_ref())
             .unwrap();
 
-        let form = multipart::Form::new()
+        let mut form = multipart::Form::new()
             .text("file_name", sync_file_info.file_name)
             .text("file_path", sync_file_info.file_path)
             .text("size", sync_file_info.size.to_string())
-            .text("last_modified", sync_file_info.last_modified.to_rfc3339())
-            .part("upload_data", file_stream);
+            .text("last_modified", sync_file_info.last_modified.to_rfc3339());
+        if let Some(base_last_modified) = base_last_modified {
+            form = form.text("base_last_modified", base_last_modified.to_rfc3339());
+        }
+        let form = form.part("upload_data", file_stream);
 
         let response = match self.client.post(url).multipart(form).send().await {
             Ok(response) => response,
             Err(_) => {
-                return;
+                return SyncUploadStatus::Failed;
             }
         };
 
+        if response.status() == StatusCode::CONFLICT {
+            return SyncUploadStatus::Conflict;
+        }
+
         if !response.status().is_success() {
             warn!(
                 "Failed to upload sync file: {} - {}",
                 response.status(),
                 response.text().await.unwrap()
             );
+            return SyncUploadStatus::Failed;
         }
+
+        SyncUploadStatus::Uploaded
     }
 
     pub async fn get_sync_file_list(
@@ -130,6 +157,140 @@ impl MinusGamesClient {
         result.json().await.ok()?
     }
 
+    pub async fn get_sync_file_history(
+        &self,
+        game: &str,
+        folder_hash: &str,
+    ) -> Option<Vec<SyncFileHistory>> {
+        let url = self
+            .url
+            .join("/sync-history/")
+            .unwrap()
+            .join(&format!("{}/{folder_hash}", encode_problem_chars(game)))
+            .unwrap();
+        let result = self.client.get(url).send().await.ok()?;
+
+        if !result.status().is_success() {
+            warn!(
+                "Failed to get sync file history: {} - {}",
+                result.status(),
+                result.text().await.unwrap()
+            );
+            return None;
+        }
+
+        result.json().await.ok()?
+    }
+
+    pub async fn restore_sync_file(
+        &self,
+        game: &str,
+        folder_hash: &str,
+        restore: &RestoreSyncFile,
+    ) -> bool {
+        let url = self
+            .url
+            .join("/sync-history/")
+            .unwrap()
+            .join(&format!(
+                "{}/{folder_hash}/restore",
+                encode_problem_chars(game)
+            ))
+            .unwrap();
+        let response = match self.client.post(url).json(restore).send().await {
+            Ok(response) => response,
+            Err(err) => {
+                warn!("Failed to restore sync file: {err}");
+                return false;
+            }
+        };
+
+        if !response.status().is_success() {
+            warn!(
+                "Failed to restore sync file: {} - {}",
+                response.status(),
+                response.text().await.unwrap()
+            );
+            return false;
+        }
+
+        true
+    }
+
+    pub async fn create_token(
+        &self,
+        username: &str,
+        password: &str,
+        device_name: &str,
+    ) -> Option<AuthToken> {
+        let url = self.url.join("/auth/token").unwrap();
+        let create_auth_token = CreateAuthToken {
+            username: username.to_string(),
+            password: password.to_string(),
+            device_name: device_name.to_string(),
+        };
+        let result = self
+            .client
+            .post(url)
+            .json(&create_auth_token)
+            .send()
+            .await
+            .ok()?;
+
+        if !result.status().is_success() {
+            warn!(
+                "Failed to create token: {} - {}",
+                result.status(),
+                result.text().await.unwrap()
+            );
+            return None;
+        }
+
+        result.json().await.ok()?
+    }
+
+    pub async fn get_tokens(&self) -> Option<Vec<AuthTokenInfo>> {
+        let result = self.call_get("/auth/tokens").await?;
+
+        if !result.status().is_success() {
+            warn!(
+                "Failed to get tokens: {} - {}",
+                result.status(),
+                result.text().await.unwrap()
+            );
+            return None;
+        }
+
+        result.json().await.ok()?
+    }
+
+    pub async fn revoke_token(&self, id: &str) -> bool {
+        let url = self
+            .url
+            .join("/auth/tokens/")
+            .unwrap()
+            .join(&encode_problem_chars(id))
+            .unwrap();
+        let response = match self.client.delete(url).send().await {
+            Ok(response) => response,
+            Err(err) => {
+                warn!("Failed to revoke token: {err}");
+                return false;
+            }
+        };
+
+        if !response.status().is_success() {
+            warn!(
+                "Failed to revoke token: {} - {}",
+                response.status(),
+                response.text().await.unwrap()
+            );
+            return false;
+        }
+
+        true
+    }
+
     pub async fn download_game_infos_if_modified(&self, game: &str) -> bool {
         let json_name = create_game_infos_name(game);
         let from = self
@@ -194,7 +355,7 @@ impl MinusGamesClient {
         folder_hash: &str,
         file_path: &str,
         to: &Path,
-    ) {
+    ) -> bool {
         let url = self
             .url
             .join("/sync/")
@@ -203,7 +364,7 @@ impl MinusGamesClient {
             .unwrap()
             .join(file_path)
             .unwrap();
-        self.download_file(url, to).await;
+        self.download_file(url, to).await
     }
 
     pub async fn download_game_additions_header_file(&self, game: &str, to: &Path) {
@@ -273,8 +434,14 @@ impl MinusGamesClient {
         true
     }
 
-    pub async fn download_file(&self, from: Url, to: &Path) {
-        let response = self.client.get(from.clone()).send().await.unwrap();
+    pub async fn download_file(&self, from: Url, to: &Path) -> bool {
+        let response = match self.client.get(from.clone()).send().await {
+            Ok(response) => response,
+            Err(err) => {
+                warn!("Failed to get from url: {from} - {err}");
+                return false;
+            }
+        };
 
         let status = response.status();
 
@@ -283,7 +450,7 @@ impl MinusGamesClient {
                 "Failed to download the file: {from} with status: {}",
                 status.as_str()
             );
-            return;
+            return false;
         }
 
         download_loop(response, to).await
@@ -297,28 +464,39 @@ impl MinusGamesClient {
         self.download_file(from, to.as_path()).await;
     }
 
-    pub fn new(url: &str, username: Option<&String>, password: Option<&String>) -> Self {
-        let client = if let Some(username_string) = username
+    pub fn new(
+        url: &str,
+        username: Option<&String>,
+        password: Option<&String>,
+        token: Option<&String>,
+        ca_bundle: Option<&PathBuf>,
+        cert_fingerprint: Option<&String>,
+    ) -> Self {
+        let authorization = if let Some(token_string) = token {
+            Some(format!("Bearer {token_string}"))
+        } else if let Some(username_string) = username
             && let Some(password_string) = password
         {
-            let mut headers = HeaderMap::new();
             let encoded_part =
                 BASE64_STANDARD.encode(format!("{}:{}", username_string, password_string));
+            Some(format!("Basic {encoded_part}"))
+        } else {
+            None
+        };
+        let mut client_builder = configure_tls(
+            reqwest::ClientBuilder::new().cookie_store(true),
+            ca_bundle.map(PathBuf::as_path),
+            cert_fingerprint.map(String::as_str),
+        );
+        if let Some(authorization) = authorization {
+            let mut headers = HeaderMap::new();
             headers.append(
                 AUTHORIZATION,
-                HeaderValue::from_str(&format!("Basic {encoded_part}")).unwrap(),
+                HeaderValue::from_str(&authorization).unwrap(),
             );
-            reqwest::ClientBuilder::new()
-                .cookie_store(true)
-                .default_headers(headers)
-                .build()
-                .unwrap()
-        } else {
-            reqwest::ClientBuilder::new()
-                .cookie_store(true)
-                .build()
-                .unwrap()
-        };
+            client_builder = client_builder.default_headers(headers);
+        }
+        let client = client_builder.build().unwrap();
         let url = Url::parse(url).unwrap_or_else(|e| {
             warn!("Failed to parse URL: {url} Error: {e}");
             OFFLINE.store(true, Relaxed);
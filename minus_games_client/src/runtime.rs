@@ -18,6 +18,7 @@ pub enum MinusGamesClientEvents {
     StartDownloadingFiles(usize),
     StartDownloadingFile,
     FinishedDownloadingFile,
+    FailedDownloadingFile(String),
     FinishedDownloadingFiles,
     LogInfoMessage(String),
     LogInfoStaticMessage(&'static str),
@@ -29,6 +30,7 @@ pub enum MinusGamesClientEvents {
     CloseGame(String),
     DownloadSaves,
     UploadSaves,
+    SaveConflict(String, String),
     Close,
 }
 
@@ -78,13 +80,22 @@ pub fn get_client() -> &'static MinusGamesClient {
                 get_config().server_url.as_str(),
                 get_config().username.as_ref(),
                 get_config().password.as_ref(),
+                get_config().token.as_ref(),
+                get_config().ca_bundle.as_ref(),
+                get_config().cert_fingerprint.as_ref(),
             )
         })
     }
 }
 
 pub async fn download_file_from_to(link: &str, to: &Path) -> bool {
-    let mut response = get_client().get(link).await;
+    let mut response = match get_client().get(link).await {
+        Ok(response) => response,
+        Err(err) => {
+            warn!("Failed to get from url: {link} - {err}");
+            return false;
+        }
+    };
 
     if !response.status().is_success() {
         return false;
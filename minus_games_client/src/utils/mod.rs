@@ -96,3 +96,13 @@ pub fn get_folders_in_path(path: &Path) -> Vec<OsString> {
         .map(|i| i.file_name().unwrap().to_os_string())
         .collect()
 }
+
+pub fn get_blake3_hash(path: &Path) -> Option<String> {
+    let file = std::fs::File::open(path).ok()?;
+    let hash = blake3::Hasher::new()
+        .update_reader(file)
+        .ok()?
+        .finalize()
+        .to_string();
+    Some(hash)
+}
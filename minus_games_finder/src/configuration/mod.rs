@@ -1,14 +1,87 @@
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+use minus_games_models::game_infos::GameInfos;
 use minus_games_utils::DataFolder;
 use minus_games_utils::{GamesFolder, get_csv_path, get_game_infos_path};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-#[derive(Parser, Debug, Serialize, Deserialize)]
+pub const MAIN_LIBRARY: &str = "main";
+
+/// A game library root, written as `name=path` or just `path`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Library {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl FromStr for Library {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, path) = match value.split_once('=') {
+            Some((name, path)) => (name.trim().to_string(), PathBuf::from(path.trim())),
+            None => {
+                let path = PathBuf::from(value.trim());
+                let name = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .ok_or(format!("Library without name: {value}"))?
+                    .to_string();
+                (name, path)
+            }
+        };
+        Library::validate_name(&name)?;
+        Ok(Self { name, path })
+    }
+}
+
+impl Library {
+    fn validate_name(name: &str) -> Result<(), String> {
+        if name.is_empty() || name == MAIN_LIBRARY {
+            return Err(format!("Invalid library name: {name}"));
+        }
+        Ok(())
+    }
+
+    /// Names have to be unique, otherwise a game can't be mapped back to its library.
+    pub fn validate(libraries: &[Library]) -> Result<(), String> {
+        let mut names = std::collections::HashSet::new();
+        for library in libraries {
+            Library::validate_name(&library.name)?;
+            if !names.insert(library.name.as_str()) {
+                return Err(format!(
+                    "Duplicate library name: {} ({})",
+                    library.name,
+                    library.path.display()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The games folder is the main library, the others follow in order.
+    pub fn with_main_library(games_folder: &Path, libraries: &[Library]) -> Vec<Library> {
+        let main_library = Library {
+            name: MAIN_LIBRARY.to_string(),
+            path: games_folder.to_path_buf(),
+        };
+        std::iter::once(main_library)
+            .chain(libraries.iter().cloned())
+            .collect()
+    }
+}
+
+#[derive(Parser, Debug, Serialize, Deserialize, Clone)]
 #[command(author, version, about, long_about = None)]
 pub struct Configuration {
     #[arg(long, default_value = GamesFolder {}, env = "MINUS_GAMES_GAMES_FOLDER")]
     pub games_folder: PathBuf,
+    /// Additional game libraries
+    #[arg(long = "library", env = "MINUS_GAMES_LIBRARIES", value_delimiter = ',')]
+    pub libraries: Vec<Library>,
     #[arg(long, default_value = DataFolder {}, env = "MINUS_GAMES_DATA_FOLDER")]
     pub data_folder: PathBuf,
     #[arg(long, env = "MINUS_GAMES_CACHE_FOLDER")]
@@ -29,9 +102,55 @@ pub struct Configuration {
     pub cleanup_data_folder: bool,
     #[arg(long, short, env = "MINUS_GAMES_FILTER")]
     pub filter: Option<String>,
+    /// Keeps running and updates games, when their folders change
+    #[arg(long, default_value = "false", env = "MINUS_GAMES_WATCH")]
+    pub watch: bool,
+    #[arg(long, env = "MINUS_GAMES_CONFIG_FILE")]
+    pub config_file: Option<String>,
 }
 
 impl Configuration {
+    /// Precedence: cli > env > config file > defaults
+    pub fn load() -> Result<Self, String> {
+        let matches = Self::command().get_matches();
+        let mut config = Self::from_arg_matches(&matches).map_err(|err| err.to_string())?;
+        if let Some(config_file) = config.config_file.as_ref() {
+            let file_configuration = FileConfiguration::read(config_file)?;
+            config.merge(file_configuration, &matches);
+        }
+        Library::validate(&config.libraries)?;
+        Ok(config)
+    }
+
+    fn merge(&mut self, file_configuration: FileConfiguration, matches: &ArgMatches) {
+        let is_default = |id: &str| {
+            !matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        };
+        macro_rules! merge {
+            ($field:ident) => {
+                if let Some(value) = file_configuration.$field
+                    && is_default(stringify!($field))
+                {
+                    self.$field = value.into();
+                }
+            };
+        }
+        merge!(games_folder);
+        merge!(libraries);
+        merge!(data_folder);
+        merge!(cache_folder);
+        merge!(keep_existing_configs);
+        merge!(cleanup_data_folder);
+        merge!(watch);
+    }
+
+    pub fn get_libraries(&self) -> Vec<Library> {
+        Library::with_main_library(&self.games_folder, &self.libraries)
+    }
+
     pub fn get_cache_file_if_exists(&self, game_folder_name: &str) -> Option<PathBuf> {
         if let Some(cache_file) = self.get_cache_file(game_folder_name)
             && cache_file.exists()
@@ -58,6 +177,14 @@ impl Configuration {
         get_csv_path(&self.data_folder, game_folder_name)
     }
 
+    pub fn get_existing_tags(&self, game_folder_name: &str) -> Option<Vec<String>> {
+        let file =
+            std::fs::File::open(self.get_game_infos_path_from_game(game_folder_name)).ok()?;
+        serde_json::from_reader::<_, GameInfos>(BufReader::new(file))
+            .ok()?
+            .tags
+    }
+
     pub fn does_game_infos_exists(&self, game: &str) -> bool {
         self.get_game_infos_path_from_game(game).is_file()
             && self.get_csv_path_from_game(game).is_file()
@@ -74,6 +201,9 @@ impl std::fmt::Display for Configuration {
                 .to_str()
                 .unwrap()
         )?;
+        for library in self.libraries.iter() {
+            writeln!(f, "Library {}: {}", library.name, library.path.display())?;
+        }
         writeln!(
             f,
             "Data Folder: {}",
@@ -92,6 +222,11 @@ impl std::fmt::Display for Configuration {
             writeln!(f, "Cache Folder: Not set")?;
         }
         writeln!(f, "Keep existing files: {}", self.keep_existing_configs)?;
+        writeln!(
+            f,
+            "Config File: {}",
+            self.config_file.as_ref().unwrap_or(&String::from("None"))
+        )?;
         if let Some(value) = &self.filter {
             write!(f, "Filter: {value}")?;
         };
@@ -102,15 +237,25 @@ impl std::fmt::Display for Configuration {
     }
 }
 
-#[derive(Parser, Debug, Serialize, Deserialize)]
-#[command(author, version, about, long_about = None)]
+/// Toml config file, every value is optional.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct FileConfiguration {
-    #[arg(long)]
-    pub ip: Option<String>,
-    #[arg(long)]
-    pub port: Option<u16>,
-    #[arg(long)]
-    pub game_folder: Option<String>,
-    #[arg(long)]
-    pub data_folder: Option<String>,
+    #[serde(alias = "game_folder")]
+    pub games_folder: Option<PathBuf>,
+    pub libraries: Option<Vec<Library>>,
+    pub data_folder: Option<PathBuf>,
+    pub cache_folder: Option<PathBuf>,
+    pub keep_existing_configs: Option<bool>,
+    pub cleanup_data_folder: Option<bool>,
+    pub watch: Option<bool>,
+}
+
+impl FileConfiguration {
+    pub fn read(path: &str) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read config file: {path} With Error: {err}"))?;
+        toml::from_str(&content)
+            .map_err(|err| format!("Failed to parse config file: {path} With Error: {err}"))
+    }
 }
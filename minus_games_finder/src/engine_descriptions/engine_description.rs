@@ -1,32 +1,152 @@
+use glob::{MatchOptions, Pattern};
 use minus_games_models::GameEngine;
 use serde::{Deserialize, Serialize};
+use std::path::{Component, Path};
 
-#[derive(Default, Debug, Serialize, Deserialize)]
-pub enum Platform {
-    #[default]
-    Windows,
-    Linux,
-}
+/// Variables, which the client resolves at the start of a sync folder.
+const SYNC_FOLDER_VARIABLES: [&str; 9] = [
+    "$GAME_ROOT",
+    "$UNITY_CONFIG",
+    "$UNREAL_CONFIG",
+    "$DOCUMENTS",
+    "$ELECTRON_CONFIG",
+    "$APPDATA_ROAMING",
+    "$APPDATA_ROAMING_OR_CONFIG",
+    "$GODOT_USER_DATA",
+    "$LOCAL_APPDATA_OR_CONFIG",
+];
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct PlatformDescription {
-    pub platform: Platform,
+    #[serde(default)]
     pub look_for_files: Vec<String>,
+    #[serde(default)]
     pub look_for_folders: Vec<String>,
+    #[serde(default)]
+    pub look_for_file_endings: Vec<FileEnding>,
+}
+
+/// A file matching the pattern has to end with the bytes, e.g. a Godot pck embedded in the executable.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileEnding {
+    pub pattern: String,
+    pub ending: String,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct EngineDescription {
+    pub name: String,
     pub engine_type: GameEngine,
+    /// Lower is checked first
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
     pub main_files: Vec<String>,
+    #[serde(default)]
     pub main_folders: Vec<String>,
     pub platform_windows: Option<PlatformDescription>,
     pub platform_linux: Option<PlatformDescription>,
+    /// Replaces the sync folders found for the engine, e.g. `$GAME_ROOT/saves`
+    pub sync_folders: Option<Vec<String>>,
+    /// Replaces the excludes of the engine
+    pub excludes: Option<Vec<String>>,
+    /// Replaces the includes of the engine, only matching files are synced
+    pub includes: Option<Vec<String>>,
+    /// Glob patterns of executables, which are never chosen
+    #[serde(default)]
+    pub exe_blacklist: Vec<String>,
+}
+
+impl EngineDescription {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("The name is empty".to_string());
+        }
+        if self.platform_windows.is_none() && self.platform_linux.is_none() {
+            return Err("Needs at least one platform".to_string());
+        }
+        for path in self.main_files.iter().chain(self.main_folders.iter()) {
+            validate_relative_path(path)?;
+        }
+        for platform in [&self.platform_windows, &self.platform_linux]
+            .into_iter()
+            .flatten()
+        {
+            for pattern in platform
+                .look_for_files
+                .iter()
+                .chain(platform.look_for_folders.iter())
+            {
+                validate_relative_path(pattern)?;
+                validate_pattern(pattern)?;
+            }
+            for file_ending in platform.look_for_file_endings.iter() {
+                validate_relative_path(&file_ending.pattern)?;
+                validate_pattern(&file_ending.pattern)?;
+                if file_ending.ending.is_empty() {
+                    return Err(format!("The ending for {} is empty", file_ending.pattern));
+                }
+            }
+        }
+        for sync_folder in self.sync_folders.iter().flatten() {
+            if !SYNC_FOLDER_VARIABLES
+                .iter()
+                .any(|variable| Path::new(sync_folder).starts_with(variable))
+            {
+                return Err(format!(
+                    "The sync folder {sync_folder} has to start with one of: {}",
+                    SYNC_FOLDER_VARIABLES.join(", ")
+                ));
+            }
+        }
+        for pattern in self
+            .exe_blacklist
+            .iter()
+            .chain(self.includes.iter().flatten())
+        {
+            validate_pattern(pattern)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_blacklisted(&self, exe: &str) -> bool {
+        let options = MatchOptions {
+            case_sensitive: false,
+            require_literal_separator: false,
+            require_literal_leading_dot: false,
+        };
+        let file_name = exe.rsplit('/').next().unwrap_or(exe);
+        self.exe_blacklist.iter().any(|pattern| {
+            Pattern::new(pattern).is_ok_and(|pattern| pattern.matches_with(file_name, options))
+        })
+    }
+}
+
+fn validate_relative_path(path: &str) -> Result<(), String> {
+    if Path::new(path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        Ok(())
+    } else {
+        Err(format!("{path} has to be relative to the game folder"))
+    }
+}
+
+fn validate_pattern(pattern: &str) -> Result<(), String> {
+    Pattern::new(pattern)
+        .map(|_| ())
+        .map_err(|err| format!("Invalid pattern {pattern}: {err}"))
 }
 
 impl std::fmt::Display for EngineDescription {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Name: {}", self.name)?;
         writeln!(f, "Game Engine: {}", self.engine_type)?;
+        writeln!(f, "Priority: {}", self.priority)?;
         writeln!(f, "Files:")?;
         for file in self.main_files.iter() {
             writeln!(f, "{file}")?;
@@ -1,152 +1,160 @@
-use crate::engine_descriptions::engine_description::{
-    EngineDescription, Platform, PlatformDescription,
-};
-use minus_games_models::GameEngine;
-use std::convert::Into;
-use std::sync::LazyLock;
+use crate::engine_descriptions::engine_description::EngineDescription;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use tracing::info;
 
 pub mod engine_description;
 
-static REN_PY: LazyLock<EngineDescription> = LazyLock::new(|| EngineDescription {
-    engine_type: GameEngine::RenPy,
-    main_files: vec![],
-    main_folders: vec!["renpy".into()],
-    platform_windows: Some(PlatformDescription {
-        platform: Platform::Windows,
-        look_for_files: vec!["*.exe".into()],
-        look_for_folders: vec!["lib/*windows*".into()],
-    }),
-    platform_linux: Some(PlatformDescription {
-        platform: Platform::Linux,
-        look_for_files: vec!["*.sh".into()],
-        look_for_folders: vec!["lib/**/*.so".into()],
-    }),
-});
+const BUILT_IN_ENGINE_DESCRIPTIONS: &str = include_str!("engine_descriptions.toml");
+/// Files in the data folder with additional engine descriptions
+const ENGINE_DESCRIPTION_FILES: [&str; 2] =
+    ["engine_descriptions.toml", "engine_descriptions.json"];
 
-static RPGM: LazyLock<EngineDescription> = LazyLock::new(|| EngineDescription {
-    engine_type: GameEngine::RPGMaker,
-    main_files: vec!["package.json".into()],
-    main_folders: vec!["www".into()],
-    platform_windows: Some(PlatformDescription {
-        platform: Platform::Windows,
-        look_for_files: vec!["*.exe".into()],
-        look_for_folders: vec![],
-    }),
-    platform_linux: Some(PlatformDescription {
-        platform: Platform::Linux,
-        look_for_files: vec![],
-        look_for_folders: vec![],
-    }),
-});
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EngineDescriptionFile {
+    #[serde(default)]
+    pub engines: Vec<EngineDescription>,
+}
 
-static ELECTRON: LazyLock<EngineDescription> = LazyLock::new(|| EngineDescription {
-    engine_type: GameEngine::Electron,
-    main_files: vec!["snapshot_blob.bin".into()],
-    main_folders: vec!["resources".into()],
-    platform_windows: Some(PlatformDescription {
-        platform: Platform::Windows,
-        look_for_files: vec!["*.exe".into()],
-        look_for_folders: vec![],
-    }),
-    platform_linux: Some(PlatformDescription {
-        platform: Platform::Linux,
-        look_for_files: vec![],
-        look_for_folders: vec![],
-    }),
-});
+pub fn get_built_in_engine_descriptions() -> Vec<EngineDescription> {
+    toml::from_str::<EngineDescriptionFile>(BUILT_IN_ENGINE_DESCRIPTIONS)
+        .expect("Invalid built-in engine descriptions")
+        .engines
+}
 
-static UNITY: LazyLock<EngineDescription> = LazyLock::new(|| EngineDescription {
-    engine_type: GameEngine::Unity,
-    main_files: vec![],
-    main_folders: vec![],
-    platform_windows: Some(PlatformDescription {
-        platform: Platform::Windows,
-        look_for_files: vec!["UnityPlayer.dll".into()],
-        look_for_folders: vec![],
-    }),
-    platform_linux: Some(PlatformDescription {
-        platform: Platform::Linux,
-        look_for_files: vec!["UnityPlayer.so".into()],
-        look_for_folders: vec![],
-    }),
-});
+/// The built-in engine descriptions merged with the ones from the data folder, sorted by priority.
+/// A description with the name of a built-in one replaces it.
+pub fn load_engine_descriptions(data_folder: &Path) -> Result<Vec<EngineDescription>, String> {
+    let mut engine_descriptions = get_built_in_engine_descriptions();
+    for file_name in ENGINE_DESCRIPTION_FILES {
+        let path = data_folder.join(file_name);
+        if !path.is_file() {
+            continue;
+        }
+        let mut names = HashSet::new();
+        for engine_description in read_engine_description_file(&path)?.engines {
+            engine_description
+                .validate()
+                .map_err(|err| format!("{} {}: {err}", path.display(), engine_description.name))?;
+            if !names.insert(engine_description.name.clone()) {
+                return Err(format!(
+                    "{}: The engine description {} exists more than once",
+                    path.display(),
+                    engine_description.name
+                ));
+            }
+            match engine_descriptions
+                .iter_mut()
+                .find(|existing| existing.name == engine_description.name)
+            {
+                Some(existing) => {
+                    info!("Replace engine description: {}", engine_description.name);
+                    *existing = engine_description;
+                }
+                None => {
+                    info!("Add engine description: {}", engine_description.name);
+                    engine_descriptions.push(engine_description);
+                }
+            }
+        }
+    }
+    engine_descriptions.sort_by_key(|engine_description| engine_description.priority);
+    Ok(engine_descriptions)
+}
 
-static UNREAL: LazyLock<EngineDescription> = LazyLock::new(|| EngineDescription {
-    engine_type: GameEngine::Unreal,
-    main_files: vec![],
-    main_folders: vec!["Engine".into()],
-    platform_windows: Some(PlatformDescription {
-        platform: Platform::Windows,
-        look_for_files: vec![],
-        look_for_folders: vec![],
-    }),
-    platform_linux: None,
-});
+fn read_engine_description_file(path: &Path) -> Result<EngineDescriptionFile, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {}. Error: {err}", path.display()))?;
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        serde_json::from_str(&content).map_err(|err| err.to_string())
+    } else {
+        toml::from_str(&content).map_err(|err| err.to_string())
+    }
+    .map_err(|err| format!("Failed to parse {}. Error: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine_descriptions::{get_built_in_engine_descriptions, load_engine_descriptions};
+    use minus_games_models::GameEngine;
 
-static UNITY_OLD: LazyLock<EngineDescription> = LazyLock::new(|| EngineDescription {
-    engine_type: GameEngine::Unity,
-    main_files: vec![],
-    main_folders: vec![],
-    platform_windows: Some(PlatformDescription {
-        platform: Platform::Windows,
-        look_for_files: vec!["*/Managed/UnityEngine.dll".into()],
-        look_for_folders: vec![],
-    }),
-    platform_linux: None,
-});
+    #[test]
+    fn test_built_in_engine_descriptions_are_valid() {
+        for engine_description in get_built_in_engine_descriptions() {
+            assert!(
+                engine_description.validate().is_ok(),
+                "{}",
+                engine_description.name
+            );
+        }
+    }
 
-static WOLF_RPG_EDITOR: LazyLock<EngineDescription> = LazyLock::new(|| EngineDescription {
-    engine_type: GameEngine::WolfRPGEditor,
-    main_files: vec!["Script.vdf".into()],
-    main_folders: vec!["Data".into()],
-    platform_windows: Some(PlatformDescription {
-        platform: Platform::Windows,
-        look_for_files: vec![],
-        look_for_folders: vec![],
-    }),
-    platform_linux: None,
-});
+    #[test]
+    fn test_engine_descriptions_from_data_folder() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("engine_descriptions.toml"),
+            r#"
+[[engines]]
+name = "Kirikiri"
+engine_type = "Kirikiri"
+priority = 5
+main_files = ["data.xp3"]
+platform_windows = {}
+sync_folders = ["$GAME_ROOT/save"]
 
-static KIRIKIRI: LazyLock<EngineDescription> = LazyLock::new(|| EngineDescription {
-    engine_type: GameEngine::Kirikiri,
-    main_files: vec!["data.xp3".into()],
-    main_folders: vec![],
-    platform_windows: Some(PlatformDescription {
-        platform: Platform::Windows,
-        look_for_files: vec![],
-        look_for_folders: vec![],
-    }),
-    platform_linux: None,
-});
+[[engines]]
+name = "Custom"
+engine_type = "Other"
+priority = 500
+platform_windows = { look_for_files = ["*.exe"] }
+exe_blacklist = ["*Launcher*.exe"]
+"#,
+        )
+        .unwrap();
 
-static OTHER: LazyLock<EngineDescription> = LazyLock::new(|| EngineDescription {
-    engine_type: GameEngine::Other,
-    main_files: vec![],
-    main_folders: vec![],
-    platform_windows: Some(PlatformDescription {
-        platform: Platform::Windows,
-        look_for_files: vec!["*.exe".into()],
-        look_for_folders: vec![],
-    }),
-    platform_linux: Some(PlatformDescription {
-        platform: Platform::Linux,
-        look_for_files: vec![],
-        look_for_folders: vec![],
-    }),
-});
+        let engine_descriptions = load_engine_descriptions(temp_dir.path()).unwrap();
+        assert_eq!(engine_descriptions.first().unwrap().name, "Kirikiri");
+        assert!(matches!(
+            engine_descriptions.first().unwrap().engine_type,
+            GameEngine::Kirikiri
+        ));
+        assert_eq!(
+            engine_descriptions
+                .iter()
+                .filter(|engine_description| engine_description.name == "Kirikiri")
+                .count(),
+            1
+        );
+        let custom = engine_descriptions
+            .iter()
+            .find(|engine_description| engine_description.name == "Custom")
+            .unwrap();
+        assert!(custom.is_blacklisted("GameLauncher.exe"));
+        assert!(!custom.is_blacklisted("Game.exe"));
+        assert_eq!(engine_descriptions.last().unwrap().name, "Other");
+    }
 
-pub fn get_game_description_for_engine(
-    game_engine: GameEngine,
-) -> Option<&'static EngineDescription> {
-    match game_engine {
-        GameEngine::RenPy => Some(&*REN_PY),
-        GameEngine::RPGMaker => Some(&*RPGM),
-        GameEngine::Unreal => Some(&*UNREAL),
-        GameEngine::Unity => Some(&*UNITY),
-        GameEngine::UnityOld => Some(&*UNITY_OLD),
-        GameEngine::WolfRPGEditor => Some(&*WOLF_RPG_EDITOR),
-        GameEngine::Electron => Some(&*ELECTRON),
-        GameEngine::Kirikiri => Some(&*KIRIKIRI),
-        GameEngine::Other => Some(&*OTHER),
+    #[test]
+    fn test_invalid_engine_descriptions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("engine_descriptions.json");
+        for engines in [
+            r#"[{"name": "Escape", "engine_type": "Other", "main_files": ["../game.exe"], "platform_windows": {}}]"#,
+            r#"[{"name": "Sync", "engine_type": "Other", "platform_windows": {}, "sync_folders": ["/saves"]}]"#,
+            r#"[{"name": "NoPlatform", "engine_type": "Other"}]"#,
+            r#"[{"name": "Unknown", "engine_type": "Other", "platform_windows": {}, "unknown": 1}]"#,
+        ] {
+            std::fs::write(&path, format!(r#"{{"engines": {engines}}}"#)).unwrap();
+            assert!(
+                load_engine_descriptions(temp_dir.path()).is_err(),
+                "{engines}"
+            );
+        }
     }
 }
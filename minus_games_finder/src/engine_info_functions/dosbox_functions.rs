@@ -0,0 +1,85 @@
+use crate::engine_info_functions::EngineInfoFunctions;
+use crate::utils::{
+    find_emulator_exe, find_possible_save_dir_in_game_root, get_gog_game_name,
+    get_gog_start_script, get_title_from_parent_folder,
+};
+use minus_games_models::game_infos::EmulatorConfig;
+use std::path::Path;
+
+const DOSBOX_EXE: &str = "dosbox.exe";
+/// GOG ships additional configs, e.g. for multiplayer, next to the main one.
+const CONFIG_SUFFIXES: [&str; 4] = ["_single", "_settings", "_client", "_server"];
+
+#[derive(Copy, Clone)]
+pub struct DOSBoxFunctions {}
+
+impl EngineInfoFunctions for DOSBoxFunctions {
+    fn get_game_name(&self, game_root: &Path) -> Option<String> {
+        get_gog_game_name(game_root).or_else(|| get_title_from_parent_folder(game_root))
+    }
+
+    fn get_linux_exe(&self, game_root: &Path) -> Option<String> {
+        get_gog_start_script(game_root)
+    }
+
+    /// The bundled DOSBox, the client passes the configs to it.
+    fn get_windows_exe(&self, game_root: &Path) -> Option<String> {
+        find_emulator_exe(game_root, DOSBOX_EXE)
+    }
+
+    /// DOS games save into their own folder.
+    fn get_sync_folders(&self, game_root: &Path) -> Option<Vec<String>> {
+        find_possible_save_dir_in_game_root(game_root).or(Some(vec!["$GAME_ROOT".to_string()]))
+    }
+
+    fn get_includes(&self, game_root: &Path) -> Option<Vec<String>> {
+        if find_possible_save_dir_in_game_root(game_root).is_some() {
+            return None;
+        }
+        Some(vec![
+            "**/*.sav".into(),
+            "**/*.sv[0-9]".into(),
+            "**/savegame*".into(),
+        ])
+    }
+
+    fn get_emulator_config(&self, game_root: &Path) -> Option<EmulatorConfig> {
+        let mut configs: Vec<String> = std::fs::read_dir(game_root)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|file_name| {
+                let file_name = file_name.to_ascii_lowercase();
+                file_name.starts_with("dosbox") && file_name.ends_with(".conf")
+            })
+            .collect();
+        configs.sort_by_key(|config| config.len());
+        let main_config = configs
+            .iter()
+            .find(|config| {
+                let stem = config.trim_end_matches(".conf");
+                !CONFIG_SUFFIXES.iter().any(|suffix| stem.ends_with(suffix))
+            })?
+            .clone();
+        let single_config = main_config.replace(".conf", "_single.conf");
+        let mut config_files = vec![main_config];
+        if configs.contains(&single_config) {
+            config_files.push(single_config);
+        }
+
+        // The mount commands of GOG configs are relative to the DOSBox folder
+        let working_folder = find_emulator_exe(game_root, DOSBOX_EXE).and_then(|exe| {
+            Path::new(&exe)
+                .parent()
+                .and_then(|parent| parent.to_str())
+                .filter(|parent| !parent.is_empty())
+                .map(|parent| parent.to_string())
+        });
+        Some(EmulatorConfig {
+            config_files,
+            working_folder,
+            target: None,
+        })
+    }
+}
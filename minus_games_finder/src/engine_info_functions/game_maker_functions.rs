@@ -0,0 +1,123 @@
+use crate::engine_info_functions::EngineInfoFunctions;
+use crate::utils::{
+    get_closest_windows_exe, get_title_from_parent_folder, is_elf, return_closed_string,
+};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+const WINDOWS_DATA: &str = "data.win";
+const LINUX_DATA: &str = "assets/game.unx";
+const MAX_NAME_LENGTH: u32 = 256;
+
+#[derive(Copy, Clone)]
+pub struct GameMakerFunctions {}
+
+impl EngineInfoFunctions for GameMakerFunctions {
+    fn get_game_name(&self, game_root: &Path) -> Option<String> {
+        match read_project_names(game_root) {
+            Some(project_names) => Some(project_names.display_name.unwrap_or(project_names.name)),
+            None => get_title_from_parent_folder(game_root),
+        }
+    }
+
+    fn get_linux_exe(&self, game_root: &Path) -> Option<String> {
+        let files: Vec<String> = std::fs::read_dir(game_root)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && is_elf(path))
+            .filter_map(|path| Some(path.file_name()?.to_str()?.to_string()))
+            .collect();
+        let name = self.get_game_name(game_root)?;
+        return_closed_string(name.as_str(), files)
+    }
+
+    fn get_windows_exe(&self, game_root: &Path) -> Option<String> {
+        let name = self.get_game_name(game_root)?;
+        get_closest_windows_exe(name.as_str(), game_root)
+    }
+
+    /// GameMaker saves to `%LOCALAPPDATA%\<name>` or `~/.config/<name>`.
+    fn get_sync_folders(&self, game_root: &Path) -> Option<Vec<String>> {
+        let project_names = read_project_names(game_root)?;
+        Some(vec![format!(
+            "$LOCAL_APPDATA_OR_CONFIG/{}",
+            project_names.name
+        )])
+    }
+}
+
+struct ProjectNames {
+    name: String,
+    display_name: Option<String>,
+}
+
+fn read_project_names(game_root: &Path) -> Option<ProjectNames> {
+    let data_file: PathBuf = [WINDOWS_DATA, LINUX_DATA]
+        .iter()
+        .map(|data_file| game_root.join(data_file))
+        .find(|data_file| data_file.is_file())?;
+    let mut reader = BufReader::new(File::open(data_file).ok()?);
+    let (gen8, gen8_length) = find_chunk(&mut reader, b"GEN8")?;
+    if gen8_length < 44 {
+        return None;
+    }
+
+    let name = read_string(&mut reader, gen8 + 40)?;
+    // Only newer versions store the display name
+    let display_name = if gen8_length >= 104 {
+        read_string(&mut reader, gen8 + 100).filter(|name| !name.is_empty())
+    } else {
+        None
+    };
+    Some(ProjectNames { name, display_name })
+}
+
+/// The data file is an IFF file, a FORM chunk with the chunks GEN8, OPTN, ... inside.
+/// Returns the position and length of the chunk data.
+fn find_chunk(reader: &mut (impl Read + Seek), name: &[u8; 4]) -> Option<(u64, u32)> {
+    let form_length = match read_chunk_header(reader)? {
+        (form, length) if &form == b"FORM" => length as u64 + 8,
+        _ => return None,
+    };
+    let mut position = 8;
+    while position + 8 <= form_length {
+        let (chunk, length) = read_chunk_header(reader)?;
+        position += 8;
+        if &chunk == name {
+            return Some((position, length));
+        }
+        position += length as u64;
+        reader.seek(SeekFrom::Start(position)).ok()?;
+    }
+    None
+}
+
+fn read_chunk_header(reader: &mut impl Read) -> Option<([u8; 4], u32)> {
+    let mut name = [0; 4];
+    reader.read_exact(&mut name).ok()?;
+    Some((name, read_u32(reader)?))
+}
+
+fn read_u32(reader: &mut impl Read) -> Option<u32> {
+    let mut buffer = [0; 4];
+    reader.read_exact(&mut buffer).ok()?;
+    Some(u32::from_le_bytes(buffer))
+}
+
+/// Strings are stored as an offset to the characters, the length is stored before them.
+fn read_string(reader: &mut (impl Read + Seek), offset_position: u64) -> Option<String> {
+    reader.seek(SeekFrom::Start(offset_position)).ok()?;
+    let offset = read_u32(reader)?;
+    reader
+        .seek(SeekFrom::Start(offset.checked_sub(4)? as u64))
+        .ok()?;
+    let length = read_u32(reader)?;
+    if length > MAX_NAME_LENGTH {
+        return None;
+    }
+    let mut buffer = vec![0; length as usize];
+    reader.read_exact(&mut buffer).ok()?;
+    String::from_utf8(buffer).ok()
+}
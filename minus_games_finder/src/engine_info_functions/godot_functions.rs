@@ -0,0 +1,87 @@
+use crate::engine_info_functions::EngineInfoFunctions;
+use crate::utils::{
+    file_ends_with, find_possible_save_dir_in_game_root, get_closest_windows_exe, glob_for_file,
+    is_elf,
+};
+use std::path::{Path, PathBuf};
+
+/// The last bytes of a pck file, also when it is embedded in the executable.
+const PCK_MAGIC: &[u8] = b"GDPC";
+const LINUX_EXTENSIONS: [&str; 5] = ["x86_64", "x86_32", "arm64", "arm32", "sh"];
+
+#[derive(Copy, Clone)]
+pub struct GodotFunctions {}
+
+impl EngineInfoFunctions for GodotFunctions {
+    /// The stem of the sidecar pck or of the executable with the embedded pck.
+    fn get_game_name(&self, game_root: &Path) -> Option<String> {
+        let pck = get_pck_or_embedded(game_root)?;
+        Some(pck.file_stem()?.to_str()?.to_string())
+    }
+
+    fn get_linux_exe(&self, game_root: &Path) -> Option<String> {
+        let name = self.get_game_name(game_root)?;
+        for extension in LINUX_EXTENSIONS {
+            let file_name = format!("{name}.{extension}");
+            if game_root.join(&file_name).is_file() {
+                return Some(file_name);
+            }
+        }
+        if game_root.join(&name).is_file() && is_elf(&game_root.join(&name)) {
+            return Some(name);
+        }
+        glob_for_file(game_root, "*.x86_64")
+    }
+
+    fn get_windows_exe(&self, game_root: &Path) -> Option<String> {
+        let name = self.get_game_name(game_root)?;
+        let file_name = format!("{name}.exe");
+        if game_root.join(&file_name).is_file() {
+            return Some(file_name);
+        }
+        get_closest_windows_exe(name.as_str(), game_root)
+    }
+
+    fn get_sync_folders(&self, game_root: &Path) -> Option<Vec<String>> {
+        let name = self.get_game_name(game_root)?;
+        let mut rtn = vec![format!("$GODOT_USER_DATA/{name}")];
+        if let Some(mut save_folder) = find_possible_save_dir_in_game_root(game_root) {
+            rtn.append(&mut save_folder);
+        }
+        Some(rtn)
+    }
+
+    fn get_excludes(&self, _: &Path) -> Option<Vec<String>> {
+        Some(vec!["logs".into(), "shader_cache".into(), "vulkan".into()])
+    }
+}
+
+/// Prefers a sidecar pck next to an executable with the same name,
+/// otherwise an executable with an embedded pck.
+fn get_pck_or_embedded(game_root: &Path) -> Option<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(game_root)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    let (pcks, executables): (Vec<PathBuf>, Vec<PathBuf>) = files.into_iter().partition(|file| {
+        file.extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pck"))
+    });
+    // Additional pcks, e.g. DLCs, have no executable with the same name
+    if let Some(pck) = pcks.into_iter().find(|pck| has_executable(pck)) {
+        return Some(pck);
+    }
+    executables
+        .into_iter()
+        .find(|file| file_ends_with(file, PCK_MAGIC))
+}
+
+fn has_executable(pck: &Path) -> bool {
+    std::iter::once("exe")
+        .chain(LINUX_EXTENSIONS)
+        .any(|extension| pck.with_extension(extension).is_file())
+        || is_elf(&pck.with_extension(""))
+}
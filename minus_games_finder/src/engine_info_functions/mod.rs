@@ -2,19 +2,30 @@ use self::{
     other_functions::OtherFunctions, unreal_functions::UnrealFunctions,
     wolf_rpg_engine_functions::WolfRPGEditorEngineFunctions,
 };
+use crate::engine_info_functions::dosbox_functions::DOSBoxFunctions;
 use crate::engine_info_functions::electron_functions::Electron;
+use crate::engine_info_functions::game_maker_functions::GameMakerFunctions;
+use crate::engine_info_functions::godot_functions::GodotFunctions;
 use crate::engine_info_functions::kirikiri_functions::KirikiriFunctions;
 use crate::engine_info_functions::ren_py_functions::RenPyFunctions;
 use crate::engine_info_functions::rpgm_functions::RPGMFunctions;
+use crate::engine_info_functions::rpgm_rgss_functions::RPGMRGSSFunctions;
+use crate::engine_info_functions::scummvm_functions::ScummVMFunctions;
 use crate::engine_info_functions::unity_functions::UnityFunctions;
 use minus_games_models::GameEngine;
+use minus_games_models::game_infos::EmulatorConfig;
 use std::path::Path;
+mod dosbox_functions;
 mod electron_functions;
 mod game_finding_utils;
+mod game_maker_functions;
+mod godot_functions;
 mod kirikiri_functions;
 mod other_functions;
 mod ren_py_functions;
 mod rpgm_functions;
+mod rpgm_rgss_functions;
+mod scummvm_functions;
 mod unity_functions;
 mod unreal_functions;
 mod wolf_rpg_engine_functions;
@@ -37,6 +48,14 @@ pub trait EngineInfoFunctions {
     fn get_excludes(&self, game_root: &Path) -> Option<Vec<String>> {
         None
     }
+    #[allow(unused_variables)]
+    fn get_includes(&self, game_root: &Path) -> Option<Vec<String>> {
+        None
+    }
+    #[allow(unused_variables)]
+    fn get_emulator_config(&self, game_root: &Path) -> Option<EmulatorConfig> {
+        None
+    }
 }
 
 pub fn get_engine_info_function_for_engine(
@@ -45,12 +64,17 @@ pub fn get_engine_info_function_for_engine(
     match engine {
         GameEngine::RenPy => Some(Box::new(RenPyFunctions {})),
         GameEngine::RPGMaker => Some(Box::new(RPGMFunctions {})),
+        GameEngine::RPGMakerRGSS => Some(Box::new(RPGMRGSSFunctions {})),
         GameEngine::Unreal => Some(Box::new(UnrealFunctions {})),
         GameEngine::Unity => Some(Box::new(UnityFunctions {})),
         GameEngine::UnityOld => Some(Box::new(UnityFunctions {})),
         GameEngine::WolfRPGEditor => Some(Box::new(WolfRPGEditorEngineFunctions {})),
         GameEngine::Electron => Some(Box::new(Electron {})),
         GameEngine::Kirikiri => Some(Box::new(KirikiriFunctions {})),
+        GameEngine::Godot => Some(Box::new(GodotFunctions {})),
+        GameEngine::GameMaker => Some(Box::new(GameMakerFunctions {})),
+        GameEngine::DOSBox => Some(Box::new(DOSBoxFunctions {})),
+        GameEngine::ScummVM => Some(Box::new(ScummVMFunctions {})),
         GameEngine::Other => Some(Box::new(OtherFunctions {})),
     }
 }
@@ -0,0 +1,52 @@
+use crate::engine_info_functions::EngineInfoFunctions;
+use crate::utils::{get_game_exe_or_exe, get_title_from_parent_folder};
+use std::path::Path;
+
+const GAME_INI: &str = "Game.ini";
+
+/// RPG Maker XP, VX and VX Ace, which run on the Ruby Game Scripting System.
+#[derive(Copy, Clone)]
+pub struct RPGMRGSSFunctions {}
+
+impl EngineInfoFunctions for RPGMRGSSFunctions {
+    fn get_game_name(&self, game_root: &Path) -> Option<String> {
+        read_title(game_root).or_else(|| get_title_from_parent_folder(game_root))
+    }
+
+    fn get_windows_exe(&self, game_root: &Path) -> Option<String> {
+        get_game_exe_or_exe(game_root)
+    }
+
+    /// The saves are stored next to the executable.
+    fn get_sync_folders(&self, _: &Path) -> Option<Vec<String>> {
+        Some(vec!["$GAME_ROOT".to_string()])
+    }
+
+    fn get_includes(&self, _: &Path) -> Option<Vec<String>> {
+        Some(vec![
+            "Save*.rxdata".into(),
+            "Save*.rvdata".into(),
+            "Save*.rvdata2".into(),
+        ])
+    }
+}
+
+/// The title in the `[Game]` section of the Game.ini, it isn't always UTF-8.
+fn read_title(game_root: &Path) -> Option<String> {
+    let content = std::fs::read(game_root.join(GAME_INI)).ok()?;
+    let content = String::from_utf8_lossy(&content);
+    let mut in_game_section = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_game_section = line.eq_ignore_ascii_case("[Game]");
+        } else if in_game_section
+            && let Some((key, value)) = line.split_once('=')
+            && key.trim().eq_ignore_ascii_case("Title")
+            && !value.trim().is_empty()
+        {
+            return Some(value.trim().to_string());
+        }
+    }
+    None
+}
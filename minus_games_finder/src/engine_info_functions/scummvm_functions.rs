@@ -0,0 +1,142 @@
+use crate::engine_info_functions::EngineInfoFunctions;
+use crate::utils::{
+    find_emulator_exe, get_gog_game_name, get_gog_start_script, get_title_from_parent_folder,
+};
+use minus_games_models::game_infos::EmulatorConfig;
+use minus_games_utils::constants::SCUMMVM_SAVES;
+use std::path::{Component, Path, PathBuf};
+
+const SCUMMVM_EXE: &str = "scummvm.exe";
+const SCUMMVM_FOLDERS: [&str; 3] = ["", "scummvm", "ScummVM"];
+
+#[derive(Copy, Clone)]
+pub struct ScummVMFunctions {}
+
+impl EngineInfoFunctions for ScummVMFunctions {
+    fn get_game_name(&self, game_root: &Path) -> Option<String> {
+        get_gog_game_name(game_root)
+            .or_else(|| read_target(game_root)?.description)
+            .or_else(|| get_title_from_parent_folder(game_root))
+    }
+
+    fn get_linux_exe(&self, game_root: &Path) -> Option<String> {
+        get_gog_start_script(game_root)
+    }
+
+    /// The bundled ScummVM, the client passes the config and the target to it.
+    fn get_windows_exe(&self, game_root: &Path) -> Option<String> {
+        find_emulator_exe(game_root, SCUMMVM_EXE)
+    }
+
+    /// The client points ScummVM to this folder for the saves.
+    fn get_sync_folders(&self, _: &Path) -> Option<Vec<String>> {
+        Some(vec![format!("$GAME_ROOT/{SCUMMVM_SAVES}")])
+    }
+
+    fn get_emulator_config(&self, game_root: &Path) -> Option<EmulatorConfig> {
+        let target = read_target(game_root)?;
+        Some(EmulatorConfig {
+            config_files: vec![target.config_file],
+            working_folder: target.data_folder,
+            target: Some(target.name),
+        })
+    }
+}
+
+struct ScummVMTarget {
+    config_file: String,
+    name: String,
+    description: Option<String>,
+    data_folder: Option<String>,
+}
+
+/// The first section of an ini file with a gameid is the target of the game.
+fn read_target(game_root: &Path) -> Option<ScummVMTarget> {
+    let mut ini_files: Vec<PathBuf> = SCUMMVM_FOLDERS
+        .iter()
+        .filter_map(|folder| std::fs::read_dir(game_root.join(folder)).ok())
+        .flat_map(|read_dir| read_dir.filter_map(|entry| entry.ok()))
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case("ini"))
+        })
+        .collect();
+    ini_files.sort();
+    ini_files.dedup();
+    ini_files
+        .iter()
+        .find_map(|ini_file| read_target_from_ini(game_root, ini_file))
+}
+
+fn read_target_from_ini(game_root: &Path, ini_file: &Path) -> Option<ScummVMTarget> {
+    let content = std::fs::read(ini_file).ok()?;
+    let content = String::from_utf8_lossy(&content);
+    let mut section: Option<&str> = None;
+    let mut values: Vec<(&str, &str)> = Vec::new();
+    let mut target: Option<&str> = None;
+    for line in content.lines().map(|line| line.trim()) {
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            if target.is_some() {
+                break;
+            }
+            section = Some(name);
+            values.clear();
+        } else if let Some(name) = section
+            && let Some((key, value)) = line.split_once('=')
+        {
+            let key = key.trim();
+            if key.eq_ignore_ascii_case("gameid") && !name.eq_ignore_ascii_case("scummvm") {
+                target = Some(name);
+            }
+            values.push((key, value.trim()));
+        }
+    }
+    let value = |key: &str| {
+        values
+            .iter()
+            .find(|(value_key, _)| value_key.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    Some(ScummVMTarget {
+        config_file: ini_file
+            .strip_prefix(game_root)
+            .ok()?
+            .to_str()?
+            .replace('\\', "/"),
+        name: target?.to_string(),
+        description: value("description"),
+        data_folder: value("path")
+            .and_then(|path| resolve_data_folder(game_root, ini_file.parent()?, path.as_str())),
+    })
+}
+
+/// Only relative paths inside the game root are kept, the configs come from Windows.
+fn resolve_data_folder(game_root: &Path, ini_folder: &Path, path: &str) -> Option<String> {
+    let path = path.replace('\\', "/");
+    let mut data_folder = ini_folder.strip_prefix(game_root).ok()?.to_path_buf();
+    for component in Path::new(&path).components() {
+        match component {
+            Component::Normal(part) => data_folder.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !data_folder.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    if path.contains(':') || !game_root.join(&data_folder).is_dir() {
+        return None;
+    }
+    let data_folder = data_folder.to_str()?.replace('\\', "/");
+    (!data_folder.is_empty()).then_some(data_folder)
+}
@@ -1,29 +1,51 @@
-use crate::configuration::Configuration;
+use crate::configuration::{Configuration, Library};
 use crate::engine_descriptions::engine_description::{EngineDescription, PlatformDescription};
-use crate::engine_descriptions::get_game_description_for_engine;
+use crate::engine_descriptions::load_engine_descriptions;
 use crate::engine_info_functions::get_engine_info_function_for_engine;
-use crate::utils::{save_game_file_infos, save_infos_to_data_folder};
+use crate::progress::FinderProgress;
+use crate::utils::{
+    HashCount, file_ends_with, find_all_possible_game_exe_files, is_folder_name, remove_game_infos,
+    return_closed_string, save_game_file_infos, save_infos_to_data_folder,
+};
+use minus_games_models::SupportedPlatforms;
+use minus_games_models::finder_job::{FinderGameResult, FinderGameStatus};
 use minus_games_models::game_infos::GameInfos;
-use minus_games_models::{GameEngine, SupportedPlatforms};
+use minus_games_models::library_conflict::LibraryConflict;
 use minus_games_utils::constants::INFOS;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
-use strum::IntoEnumIterator;
 use tracing::{info, trace, warn};
 
 pub mod configuration;
-mod engine_descriptions;
+pub mod engine_descriptions;
 mod engine_info_functions;
+pub mod progress;
 mod utils;
+pub mod watcher;
 
 pub fn run(config: Configuration) -> ExitCode {
+    match run_with_progress(config, &mut ()) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(err) => {
+            warn!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+pub fn run_with_progress(
+    config: Configuration,
+    progress: &mut impl FinderProgress,
+) -> Result<(), String> {
     info!("Start:\nConfig:\n{config}");
-    if !config.games_folder.is_dir() {
-        warn!("Game folder does not exist");
-        return ExitCode::FAILURE;
+    let libraries = config.get_libraries();
+    if !libraries.iter().any(|library| library.path.is_dir()) {
+        return Err("Game folder does not exist".to_string());
     }
+    let engine_descriptions = load_engine_descriptions(&config.data_folder)?;
 
     if config.cleanup_data_folder {
         let pattern_json = config
@@ -51,54 +73,207 @@ pub fn run(config: Configuration) -> ExitCode {
         }
     }
 
-    let root_folders: Vec<PathBuf> = config
-        .games_folder
-        .read_dir()
-        .expect("Failed to read game folder")
-        .map(|rd| rd.unwrap().path())
-        .filter(|i| i.is_dir())
-        .collect();
-
-    for folder in root_folders {
-        let folder_name = folder.iter().next_back().unwrap().to_str().unwrap();
+    for conflict in find_library_conflicts(&libraries) {
+        warn!(
+            "Game {} exists in the libraries: {}. Only the first one is used",
+            conflict.game,
+            conflict.libraries.join(", ")
+        );
+    }
 
-        if config
-            .filter
-            .as_ref()
-            .is_some_and(|f| f.as_str() != folder_name)
-        {
-            continue;
+    // Collect the games first, so the total is known
+    let mut found_games: HashMap<String, &Library> = HashMap::new();
+    let mut games: Vec<(&Library, PathBuf, String)> = Vec::new();
+    let mut shadowed_games: Vec<(String, &Library)> = Vec::new();
+    for library in libraries.iter() {
+        for folder in get_game_folders(library) {
+            let folder_name = match get_folder_name(&folder) {
+                Ok(folder_name) => folder_name,
+                Err(err) => {
+                    warn!("{err}");
+                    continue;
+                }
+            };
+            if config
+                .filter
+                .as_ref()
+                .is_some_and(|f| f.as_str() != folder_name)
+            {
+                continue;
+            }
+            if let Some(first_library) = found_games.get(&folder_name) {
+                shadowed_games.push((folder_name, first_library));
+                continue;
+            }
+            found_games.insert(folder_name.clone(), library);
+            games.push((library, folder, folder_name));
         }
+    }
 
+    // An unreadable library could be unmounted, so its games are not gone
+    let removed_game = config.filter.as_ref().filter(|game| {
+        games.is_empty()
+            && shadowed_games.is_empty()
+            && is_folder_name(game)
+            && libraries
+                .iter()
+                .all(|library| library.path.read_dir().is_ok())
+    });
+    progress.set_total(games.len() + shadowed_games.len() + usize::from(removed_game.is_some()));
+
+    for (game, first_library) in shadowed_games {
+        progress.finish_game(FinderGameResult {
+            game,
+            status: FinderGameStatus::Skipped,
+            engine: None,
+            reason: Some(format!("Already exists in library {}", first_library.name)),
+            reused_hashes: 0,
+            hashed_files: 0,
+        });
+    }
+
+    if let Some(game) = removed_game {
+        info!("Game {game} was removed");
+        remove_game_infos(&config.data_folder, game);
+        progress.finish_game(FinderGameResult {
+            game: game.to_string(),
+            status: FinderGameStatus::Removed,
+            engine: None,
+            reason: None,
+            reused_hashes: 0,
+            hashed_files: 0,
+        });
+    }
+
+    for (library, folder, folder_name) in games {
+        progress.start_game(&folder_name);
         info!(
             "Check path: {}",
             std::path::absolute(&folder).unwrap().display()
         );
-        if let Some(game_infos) = detect_game(folder.as_path(), &config) {
-            if !config.keep_existing_configs
-                || !config.does_game_infos_exists(&game_infos.folder_name)
-            {
-                save_game_file_infos(folder.as_path(), &config, &game_infos);
-                save_infos_to_data_folder(config.data_folder.as_path(), &game_infos);
-                info!("Game Infos:\n{game_infos}");
+        let result = match detect_game(folder.as_path(), &config, &engine_descriptions) {
+            Ok(mut game_infos) => {
+                // Tags are maintained by hand, so they survive a rerun of the finder.
+                if game_infos.tags.is_none() {
+                    game_infos.tags = config.get_existing_tags(&game_infos.folder_name);
+                }
+                game_infos.library = Some(library.name.clone());
+                let (status, hash_count) = if !config.keep_existing_configs
+                    || !config.does_game_infos_exists(&game_infos.folder_name)
+                {
+                    let hash_count = save_game_file_infos(
+                        folder.as_path(),
+                        &library.path,
+                        &config,
+                        &game_infos,
+                        progress,
+                    );
+                    save_infos_to_data_folder(config.data_folder.as_path(), &game_infos);
+                    info!("Game Infos:\n{game_infos}");
+                    (FinderGameStatus::Updated, hash_count)
+                } else {
+                    (FinderGameStatus::Unchanged, HashCount::default())
+                };
+                FinderGameResult {
+                    game: folder_name,
+                    status,
+                    engine: Some(game_infos.engine),
+                    reason: None,
+                    reused_hashes: hash_count.reused,
+                    hashed_files: hash_count.hashed,
+                }
+            }
+            Err(reason) => {
+                warn!("Failed to detect game at {}: {reason}", folder.display());
+                FinderGameResult {
+                    game: folder_name,
+                    status: FinderGameStatus::Failed,
+                    engine: None,
+                    reason: Some(reason),
+                    reused_hashes: 0,
+                    hashed_files: 0,
+                }
+            }
+        };
+        progress.finish_game(result);
+    }
+
+    Ok(())
+}
+
+/// Reruns the finder for a single game or removes its infos, if the game folder is gone.
+pub fn update_game(config: &Configuration, game: &str) -> ExitCode {
+    run(Configuration {
+        keep_existing_configs: false,
+        cleanup_data_folder: false,
+        filter: Some(game.to_string()),
+        watch: false,
+        ..config.clone()
+    })
+}
+
+/// Checks every game again, like [`update_game`] does for a single one.
+pub fn update_all_games(config: &Configuration) -> ExitCode {
+    run(Configuration {
+        keep_existing_configs: false,
+        cleanup_data_folder: false,
+        filter: None,
+        watch: false,
+        ..config.clone()
+    })
+}
+
+/// Game folders with the same name in more than one library.
+pub fn find_library_conflicts(libraries: &[Library]) -> Vec<LibraryConflict> {
+    let mut games: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for library in libraries {
+        for folder in get_game_folders(library) {
+            if let Some(folder_name) = folder.file_name().and_then(|name| name.to_str()) {
+                games
+                    .entry(folder_name.to_string())
+                    .or_default()
+                    .push(library.name.clone());
             }
-        } else {
-            warn!("Failed to detect game at {}", folder.display());
         }
     }
+    games
+        .into_iter()
+        .filter(|(_, libraries)| libraries.len() > 1)
+        .map(|(game, libraries)| LibraryConflict { game, libraries })
+        .collect()
+}
 
-    ExitCode::SUCCESS
+fn get_game_folders(library: &Library) -> Vec<PathBuf> {
+    let Ok(read_dir) = library.path.read_dir() else {
+        warn!(
+            "Failed to read library {}: {}",
+            library.name,
+            library.path.display()
+        );
+        return Vec::new();
+    };
+    let mut folders: Vec<PathBuf> = read_dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    folders.sort();
+    folders
 }
 
-fn detect_game(game_path: &Path, config: &Configuration) -> Option<GameInfos> {
-    let game_folder_name = game_path.iter().next_back()?.to_str()?.to_string();
+fn detect_game(
+    game_path: &Path,
+    config: &Configuration,
+    engine_descriptions: &[EngineDescription],
+) -> Result<GameInfos, String> {
+    let game_folder_name = get_folder_name(game_path)?;
 
     // Check if we have a cached infos file for this game.
     if let Some(cache_file) = config.get_cache_file_if_exists(&game_folder_name) {
         let file = File::open(cache_file.as_path()).unwrap();
         let buf = BufReader::new(file);
         match serde_json::from_reader(buf) {
-            Ok(infos) => return Some(infos),
+            Ok(infos) => return Ok(infos),
             Err(err) => {
                 warn!(
                     "Failed to parse cached infos: {} with {}. Continue with other detection methods.",
@@ -111,21 +286,19 @@ fn detect_game(game_path: &Path, config: &Configuration) -> Option<GameInfos> {
 
     let mut current_supported_platforms = None;
     let mut current_engine_description: Option<&EngineDescription> = None;
-    for engine in GameEngine::iter() {
-        if let Some(engine_description) = get_game_description_for_engine(engine) {
+    for engine_description in engine_descriptions {
+        if let Some(supported_platforms) =
+            game_path_fits_game_description(game_path, engine_description)
+        {
+            trace!("Game uses the engine {}", engine_description.name);
             current_engine_description = Some(engine_description);
-            if let Some(supported_platforms) =
-                game_path_fits_game_description(game_path, engine_description)
-            {
-                trace!("Game uses the engine {}", engine_description.engine_type);
-                current_supported_platforms = Some(supported_platforms);
-                break;
-            }
+            current_supported_platforms = Some(supported_platforms);
+            break;
         }
     }
 
     if !current_supported_platforms.is_some_and(|i| i.linux || i.windows) {
-        return None;
+        return Err("No supported engine found".to_string());
     }
 
     let mut current_name = None;
@@ -133,6 +306,8 @@ fn detect_game(game_path: &Path, config: &Configuration) -> Option<GameInfos> {
     let mut current_windows_exe = None;
     let mut current_sync_folders = None;
     let mut current_excludes = None;
+    let mut current_includes = None;
+    let mut current_emulator_config = None;
 
     if let Some(ced) = current_engine_description
         && let Some(engine_functions) = get_engine_info_function_for_engine(ced.engine_type)
@@ -142,36 +317,59 @@ fn detect_game(game_path: &Path, config: &Configuration) -> Option<GameInfos> {
             current_name = Some(name);
         }
 
-        current_name.as_ref()?;
+        if current_name.is_none() {
+            return Err("Game name not found".to_string());
+        }
 
-        if current_supported_platforms?.windows
-            && let Some(name) = engine_functions.get_windows_exe(game_path)
+        if current_supported_platforms.is_some_and(|i| i.windows)
+            && let Some(exe) = engine_functions.get_windows_exe(game_path)
         {
-            current_windows_exe = Some(name);
+            let name = current_name.as_deref().unwrap_or_default();
+            current_windows_exe = replace_blacklisted_windows_exe(exe, name, game_path, ced);
         }
 
-        if current_supported_platforms?.linux
-            && let Some(name) = engine_functions.get_linux_exe(game_path)
+        if current_supported_platforms.is_some_and(|i| i.linux)
+            && let Some(exe) = engine_functions.get_linux_exe(game_path)
+            && !ced.is_blacklisted(&exe)
         {
-            current_linux_exe = Some(name);
+            current_linux_exe = Some(exe);
         }
 
-        if current_windows_exe.is_none() && current_linux_exe.is_none() {
-            return None;
+        // Config only packages run with the local emulator
+        current_emulator_config = engine_functions.get_emulator_config(game_path);
+        if current_windows_exe.is_none()
+            && current_linux_exe.is_none()
+            && current_emulator_config.is_none()
+        {
+            return Err("No executable found".to_string());
         }
 
-        current_sync_folders = engine_functions.get_sync_folders(game_path);
+        current_sync_folders = ced
+            .sync_folders
+            .clone()
+            .or_else(|| engine_functions.get_sync_folders(game_path));
 
-        current_excludes = engine_functions.get_excludes(game_path);
+        current_excludes = ced
+            .excludes
+            .clone()
+            .or_else(|| engine_functions.get_excludes(game_path));
+
+        current_includes = ced
+            .includes
+            .clone()
+            .or_else(|| engine_functions.get_includes(game_path));
     }
 
-    let name = current_name?;
+    let name = current_name.ok_or("Game name not found")?;
 
-    if current_linux_exe.is_none() && current_windows_exe.is_none() {
-        return None;
+    if current_linux_exe.is_none()
+        && current_windows_exe.is_none()
+        && current_emulator_config.is_none()
+    {
+        return Err("No executable found".to_string());
     }
 
-    let folder_name = game_path.iter().next_back()?.to_str()?.to_string();
+    let folder_name = get_folder_name(game_path)?;
 
     // let additions = if data_folder
     //     .join(ADDITIONS)
@@ -184,10 +382,12 @@ fn detect_game(game_path: &Path, config: &Configuration) -> Option<GameInfos> {
     //     None
     // };
 
-    Some(GameInfos {
+    let engine_description = current_engine_description.ok_or("No supported engine found")?;
+
+    Ok(GameInfos {
         name,
         folder_name,
-        engine: current_engine_description?.engine_type,
+        engine: engine_description.engine_type,
         // supported_platforms: SupportedPlatforms {
         //     windows: current_windows_exe.is_some(),
         //     linux: current_linux_exe.is_some(),
@@ -196,10 +396,39 @@ fn detect_game(game_path: &Path, config: &Configuration) -> Option<GameInfos> {
         windows_exe: current_windows_exe,
         sync_folders: current_sync_folders,
         excludes: current_excludes,
+        includes: current_includes,
+        emulator_config: current_emulator_config,
+        tags: None,
+        library: None,
         // additions,
     })
 }
 
+/// Chooses the executable closest to the game name instead, if the one found for the engine is blacklisted.
+fn replace_blacklisted_windows_exe(
+    exe: String,
+    name: &str,
+    game_path: &Path,
+    engine_description: &EngineDescription,
+) -> Option<String> {
+    if !engine_description.is_blacklisted(&exe) {
+        return Some(exe);
+    }
+    let files = find_all_possible_game_exe_files(game_path)
+        .into_iter()
+        .filter(|file| !engine_description.is_blacklisted(file))
+        .collect();
+    return_closed_string(name, files)
+}
+
+fn get_folder_name(game_path: &Path) -> Result<String, String> {
+    game_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_string())
+        .ok_or_else(|| format!("Invalid folder name: {}", game_path.display()))
+}
+
 fn game_path_fits_game_description(
     game_path: &Path,
     engine_description: &EngineDescription,
@@ -256,9 +485,276 @@ fn support_platform(
                 return false;
             }
         }
+        for file_ending in platform_description.look_for_file_endings.iter() {
+            let path = game_path
+                .join(&file_ending.pattern)
+                .to_str()
+                .expect("Failed to create a file search path")
+                .to_string();
+            trace!("Glob Path: {}", &path);
+            let mut findings = glob::glob(&path).expect("Failed to read glob pattern");
+            if !findings.any(|finding| {
+                finding.is_ok_and(|file| file_ends_with(&file, file_ending.ending.as_bytes()))
+            }) {
+                return false;
+            }
+        }
     } else {
         return false;
     }
 
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::configuration::Configuration;
+    use crate::detect_game;
+    use crate::engine_descriptions::get_built_in_engine_descriptions;
+    use minus_games_models::GameEngine;
+    use minus_games_models::game_infos::GameInfos;
+
+    /// The first bytes of a ELF 64 executable
+    const ELF: &[u8] = &[0x7f, 0x45, 0x4c, 0x46, 0x02, 0x01, 0x01, 0x00];
+
+    fn detect(files: &[(&str, &[u8])]) -> Result<GameInfos, String> {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let game_path = temp_dir.path().join("games").join("Game");
+        for (file, content) in files {
+            let path = game_path.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let config = Configuration {
+            games_folder: temp_dir.path().join("games"),
+            libraries: Vec::new(),
+            data_folder: temp_dir.path().join("data"),
+            cache_folder: None,
+            keep_existing_configs: false,
+            cleanup_data_folder: false,
+            filter: None,
+            watch: false,
+            config_file: None,
+        };
+        detect_game(&game_path, &config, &get_built_in_engine_descriptions())
+    }
+
+    #[test]
+    fn test_detect_godot_sidecar_pck() {
+        let infos = detect(&[
+            ("MyGame.pck", b"GDPC"),
+            ("MyGame.exe", b"MZ"),
+            ("MyGame.x86_64", ELF),
+        ])
+        .unwrap();
+        assert!(matches!(infos.engine, GameEngine::Godot));
+        assert_eq!(infos.name, "MyGame");
+        assert_eq!(infos.windows_exe.as_deref(), Some("MyGame.exe"));
+        assert_eq!(infos.linux_exe.as_deref(), Some("MyGame.x86_64"));
+    }
+
+    #[test]
+    fn test_detect_godot_ignores_dlc_pck() {
+        let infos = detect(&[
+            ("Bonus.pck", b"GDPC"),
+            ("MyGame.pck", b"GDPC"),
+            ("MyGame.exe", b"MZ"),
+        ])
+        .unwrap();
+        assert_eq!(infos.name, "MyGame");
+        assert_eq!(infos.windows_exe.as_deref(), Some("MyGame.exe"));
+    }
+
+    #[test]
+    fn test_detect_godot_embedded_pck() {
+        let infos = detect(&[("MyGame.exe", b"MZ data GDPC"), ("Bonus.pck", b"GDPC")]).unwrap();
+        assert!(matches!(infos.engine, GameEngine::Godot));
+        assert_eq!(infos.name, "MyGame");
+        assert_eq!(infos.windows_exe.as_deref(), Some("MyGame.exe"));
+
+        let infos = detect(&[("MyGame.x86_64", b"\x7fELF\x02\x01\x01 data GDPC")]).unwrap();
+        assert_eq!(infos.name, "MyGame");
+        assert_eq!(infos.linux_exe.as_deref(), Some("MyGame.x86_64"));
+    }
+
+    /// A data file with the GEN8 chunk, its strings point into the STRG chunk.
+    fn create_game_maker_data(name: &str, display_name: &str) -> Vec<u8> {
+        let name_offset: u32 = 8 + 8 + 104 + 8 + 4;
+        let display_name_offset = name_offset + name.len() as u32 + 1 + 4;
+        let mut gen8 = vec![0; 104];
+        gen8[40..44].copy_from_slice(&name_offset.to_le_bytes());
+        gen8[100..104].copy_from_slice(&display_name_offset.to_le_bytes());
+        let mut strg = Vec::new();
+        for string in [name, display_name] {
+            strg.extend((string.len() as u32).to_le_bytes());
+            strg.extend(string.as_bytes());
+            strg.push(0);
+        }
+        let mut chunks = Vec::new();
+        for (chunk, data) in [(b"GEN8", gen8), (b"STRG", strg)] {
+            chunks.extend(chunk);
+            chunks.extend((data.len() as u32).to_le_bytes());
+            chunks.extend(data);
+        }
+        let mut rtn = b"FORM".to_vec();
+        rtn.extend((chunks.len() as u32).to_le_bytes());
+        rtn.extend(chunks);
+        rtn
+    }
+
+    #[test]
+    fn test_detect_game_maker_windows() {
+        let data = create_game_maker_data("my_game", "My Game");
+        let infos = detect(&[("data.win", &data), ("MyGame.exe", b"MZ")]).unwrap();
+        assert!(matches!(infos.engine, GameEngine::GameMaker));
+        assert_eq!(infos.name, "My Game");
+        assert_eq!(infos.windows_exe.as_deref(), Some("MyGame.exe"));
+        assert_eq!(
+            infos.sync_folders,
+            Some(vec!["$LOCAL_APPDATA_OR_CONFIG/my_game".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_detect_game_maker_linux() {
+        let data = create_game_maker_data("my_game", "");
+        let infos = detect(&[("assets/game.unx", &data), ("MyGame", ELF)]).unwrap();
+        assert!(matches!(infos.engine, GameEngine::GameMaker));
+        assert_eq!(infos.name, "my_game");
+        assert_eq!(infos.linux_exe.as_deref(), Some("MyGame"));
+        assert_eq!(infos.windows_exe, None);
+    }
+
+    #[test]
+    fn test_detect_rpg_maker_rgss() {
+        let infos = detect(&[
+            (
+                "Game.ini",
+                b"[Game]\r\nLibrary=System\\RGSS301.dll\r\nTitle=My Game\r\n",
+            ),
+            ("Game.exe", b"MZ"),
+            ("System/RGSS301.dll", b"MZ"),
+            ("Save01.rvdata2", b""),
+        ])
+        .unwrap();
+        assert!(matches!(infos.engine, GameEngine::RPGMakerRGSS));
+        assert_eq!(infos.name, "My Game");
+        assert_eq!(infos.windows_exe.as_deref(), Some("Game.exe"));
+        assert_eq!(infos.sync_folders, Some(vec!["$GAME_ROOT".to_string()]));
+        assert!(
+            infos
+                .includes
+                .unwrap()
+                .contains(&"Save*.rvdata2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_detect_rpg_maker_rgss_archive() {
+        let infos = detect(&[
+            ("Game.ini", b"[Game]\nScripts=Data\\Scripts.rxdata\n"),
+            ("Game.exe", b"MZ"),
+            ("Game.rgssad", b""),
+        ])
+        .unwrap();
+        assert!(matches!(infos.engine, GameEngine::RPGMakerRGSS));
+        // Without a title, the folder name is used
+        assert_eq!(infos.name, "Game");
+        assert_eq!(infos.windows_exe.as_deref(), Some("Game.exe"));
+    }
+
+    const GOG_INFO: &[u8] = br#"{"gameId": "1", "name": "My DOS Game"}"#;
+
+    #[test]
+    fn test_detect_dosbox_windows() {
+        let infos = detect(&[
+            ("goggame-1.info", GOG_INFO),
+            ("dosboxGame.conf", b""),
+            ("dosboxGame_single.conf", b""),
+            ("dosboxGame_settings.conf", b""),
+            ("DOSBOX/dosbox.exe", b"MZ"),
+            ("GAME/GAME.EXE", b"MZ"),
+        ])
+        .unwrap();
+        assert!(matches!(infos.engine, GameEngine::DOSBox));
+        assert_eq!(infos.name, "My DOS Game");
+        assert_eq!(infos.windows_exe.as_deref(), Some("DOSBOX/dosbox.exe"));
+        assert_eq!(infos.linux_exe, None);
+        let emulator_config = infos.emulator_config.unwrap();
+        assert_eq!(
+            emulator_config.config_files,
+            ["dosboxGame.conf", "dosboxGame_single.conf"]
+        );
+        assert_eq!(emulator_config.working_folder.as_deref(), Some("DOSBOX"));
+    }
+
+    #[test]
+    fn test_detect_dosbox_linux() {
+        let infos = detect(&[
+            ("goggame-1.info", GOG_INFO),
+            ("dosboxGame.conf", b""),
+            ("data/GAME.DAT", b""),
+            ("start.sh", b"#!/bin/sh"),
+        ])
+        .unwrap();
+        assert!(matches!(infos.engine, GameEngine::DOSBox));
+        assert_eq!(infos.linux_exe.as_deref(), Some("start.sh"));
+        assert_eq!(infos.windows_exe, None);
+
+        // Only the config, the local DOSBox runs it
+        let infos = detect(&[("dosboxGame.conf", b""), ("data/GAME.DAT", b"")]).unwrap();
+        assert!(matches!(infos.engine, GameEngine::DOSBox));
+        assert_eq!((infos.linux_exe, infos.windows_exe), (None, None));
+        assert_eq!(
+            infos.emulator_config.unwrap().config_files,
+            ["dosboxGame.conf"]
+        );
+    }
+
+    const SCUMMVM_INI: &[u8] = b"[scummvm]\r\ngameid=\r\n[sky]\r\ngameid=sky\r\ndescription=Beneath a Steel Sky\r\npath=..\\data\r\n";
+
+    #[test]
+    fn test_detect_scummvm_windows() {
+        let infos = detect(&[
+            ("ScummVM/scummvm.exe", b"MZ"),
+            ("ScummVM/sky.ini", SCUMMVM_INI),
+            ("data/sky.dsk", b""),
+        ])
+        .unwrap();
+        assert!(matches!(infos.engine, GameEngine::ScummVM));
+        assert_eq!(infos.name, "Beneath a Steel Sky");
+        assert_eq!(infos.windows_exe.as_deref(), Some("ScummVM/scummvm.exe"));
+        let emulator_config = infos.emulator_config.unwrap();
+        assert_eq!(emulator_config.config_files, ["ScummVM/sky.ini"]);
+        assert_eq!(emulator_config.working_folder.as_deref(), Some("data"));
+        assert_eq!(emulator_config.target.as_deref(), Some("sky"));
+    }
+
+    #[test]
+    fn test_detect_scummvm_linux() {
+        let infos = detect(&[("scummvm/sky.ini", SCUMMVM_INI), ("data/sky.dsk", b"")]).unwrap();
+        assert!(matches!(infos.engine, GameEngine::ScummVM));
+        assert_eq!((infos.linux_exe, infos.windows_exe), (None, None));
+        assert_eq!(
+            infos.emulator_config.unwrap().target.as_deref(),
+            Some("sky")
+        );
+
+        let infos = detect(&[
+            ("sky.ini", b"[sky]\ngameid=sky\n"),
+            ("start.sh", b"#!/bin/sh"),
+        ])
+        .unwrap();
+        assert!(matches!(infos.engine, GameEngine::ScummVM));
+        assert_eq!(infos.linux_exe.as_deref(), Some("start.sh"));
+        let emulator_config = infos.emulator_config.unwrap();
+        assert_eq!(emulator_config.config_files, ["sky.ini"]);
+        assert_eq!(emulator_config.working_folder, None);
+    }
+
+    #[test]
+    fn test_detect_ini_without_scummvm() {
+        let infos = detect(&[("settings.ini", b"[video]\n"), ("MyGame.exe", b"MZ")]).unwrap();
+        assert!(!matches!(infos.engine, GameEngine::ScummVM));
+    }
+}
@@ -1,25 +1,46 @@
-use clap::Parser;
-
 use minus_games_finder::configuration::Configuration;
-use minus_games_finder::run;
+use minus_games_finder::watcher::watch_libraries;
+use minus_games_finder::{run, update_all_games, update_game};
 use minus_games_utils::constants::INFOS;
 use std::process::ExitCode;
 use tracing::{Level, error, info};
 
 fn main() -> ExitCode {
     dotenvy::dotenv().ok();
-    let config: Configuration = Configuration::parse();
+    let config_result = Configuration::load();
 
     tracing_subscriber::fmt()
         .with_max_level(Level::DEBUG)
         .init();
 
+    let config = match config_result {
+        Ok(config) => config,
+        Err(err) => {
+            error!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
     if let Err(err) = create_necessary_folders(&config) {
         error!("Failed to create all necessary directories. Error: {}", err);
         return ExitCode::FAILURE;
     }
 
-    run(config)
+    let exit_code = run(config.clone());
+    if config.watch
+        && let Err(err) = watch_libraries(&config.get_libraries(), |game| match game {
+            Some(game) => {
+                update_game(&config, game);
+            }
+            None => {
+                update_all_games(&config);
+            }
+        })
+    {
+        error!("Failed to watch the libraries. Error: {err}");
+        return ExitCode::FAILURE;
+    }
+    exit_code
 }
 
 fn create_necessary_folders(config: &Configuration) -> Result<(), std::io::Error> {
@@ -0,0 +1,12 @@
+use minus_games_models::finder_job::FinderGameResult;
+use std::path::Path;
+
+/// Gets notified about the progress of a finder run.
+pub trait FinderProgress {
+    fn set_total(&mut self, _total: usize) {}
+    fn start_game(&mut self, _game: &str) {}
+    fn hash_file(&mut self, _file: &Path) {}
+    fn finish_game(&mut self, _result: FinderGameResult) {}
+}
+
+impl FinderProgress for () {}
@@ -1,15 +1,20 @@
 use crate::configuration::Configuration;
+use crate::progress::FinderProgress;
 use convert_case::{Case, Casing};
 use glob::MatchOptions;
 use minus_games_models::game_file_info::GameFileInfo;
 use minus_games_models::game_infos::GameInfos;
 use minus_games_utils::{create_file_list, get_csv_path, get_game_infos_path};
+use std::collections::HashMap;
 use std::ffi::OsStr;
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
+use std::num::NonZero;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
 use textdistance::str::damerau_levenshtein;
-use tracing::{trace, warn};
+use tracing::{info, trace, warn};
 use walkdir::WalkDir;
 
 pub(crate) fn is_elf(path: &Path) -> bool {
@@ -31,6 +36,17 @@ pub(crate) fn is_elf(path: &Path) -> bool {
     buffer == ELF_64
 }
 
+pub(crate) fn file_ends_with(path: &Path, ending: &[u8]) -> bool {
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return false;
+    };
+    if file.seek(SeekFrom::End(-(ending.len() as i64))).is_err() {
+        return false;
+    }
+    let mut buffer = vec![0; ending.len()];
+    file.read_exact(&mut buffer).is_ok() && buffer == ending
+}
+
 pub fn find_name_in_folder_name(folder_name: &str) -> String {
     let mut end = 0;
 
@@ -65,27 +81,131 @@ pub fn find_closest_string(close_to: &str, list: &[String]) -> usize {
     rtn
 }
 
-pub fn save_game_file_infos(game_folder: &Path, config: &Configuration, game_infos: &GameInfos) {
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HashCount {
+    pub reused: usize,
+    pub hashed: usize,
+}
+
+/// Writes the file list of a game. Hashes of files with the same path, size and
+/// modification time as in the existing csv are reused, the rest is hashed in parallel.
+pub fn save_game_file_infos(
+    game_folder: &Path,
+    library_path: &Path,
+    config: &Configuration,
+    game_infos: &GameInfos,
+    progress: &mut impl FinderProgress,
+) -> HashCount {
     let file_list = create_file_list(game_folder);
 
     let csv_path = get_csv_path(&config.data_folder, game_infos.folder_name.as_str());
     let csv_path_parent = csv_path.parent().unwrap();
     if let Err(err) = std::fs::create_dir_all(csv_path_parent) {
         warn!("Failed to create client data folder: {}", err);
-        return;
+        return HashCount::default();
     };
-    let mut csv_writer = csv::Writer::from_path(csv_path.as_path()).unwrap();
-    let cut_off = std::path::absolute(config.games_folder.as_path())
-        .unwrap()
-        .iter()
-        .count();
+    let existing_file_infos = read_existing_file_infos(&csv_path);
+    let cut_off = std::path::absolute(library_path).unwrap().iter().count();
 
+    let mut hash_count = HashCount::default();
+    let mut file_infos = Vec::with_capacity(file_list.len());
+    let mut files_to_hash = Vec::new();
     for file in file_list {
-        let file_info: GameFileInfo = GameFileInfo::from_path_buf_with_cut_off(file, cut_off);
+        let mut file_info = GameFileInfo::from_path_without_hash(&file, cut_off);
+        match existing_file_infos
+            .get(&file_info.file_path)
+            .filter(|existing| existing.is_unchanged(&file_info))
+        {
+            Some(existing) => {
+                file_info.hash = existing.hash.clone();
+                hash_count.reused += 1;
+            }
+            None => files_to_hash.push((file_infos.len(), file)),
+        }
+        file_infos.push(file_info);
+    }
+    hash_count.hashed = files_to_hash.len();
+
+    for (idx, hash) in hash_files(&files_to_hash, progress) {
+        file_infos[idx].hash = hash;
+    }
+
+    let mut csv_writer = csv::Writer::from_path(csv_path.as_path()).unwrap();
+    for file_info in file_infos {
         trace!("Game File Info: {file_info}");
         csv_writer.serialize(file_info).unwrap()
     }
     csv_writer.flush().unwrap();
+    info!(
+        "{}: reused {} hashes, hashed {} files",
+        game_infos.folder_name, hash_count.reused, hash_count.hashed
+    );
+    hash_count
+}
+
+fn read_existing_file_infos(csv_path: &Path) -> HashMap<String, GameFileInfo> {
+    let Ok(mut reader) = csv::ReaderBuilder::new().from_path(csv_path) else {
+        return HashMap::new();
+    };
+    reader
+        .deserialize::<GameFileInfo>()
+        .flatten()
+        .map(|file_info| (file_info.file_path.clone(), file_info))
+        .collect()
+}
+
+/// Hashes the files on all cores, the progress is reported from the calling thread.
+fn hash_files(
+    files: &[(usize, PathBuf)],
+    progress: &mut impl FinderProgress,
+) -> Vec<(usize, String)> {
+    let threads = std::thread::available_parallelism()
+        .map(NonZero::get)
+        .unwrap_or(1)
+        .min(files.len());
+    let next_file = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            let sender = sender.clone();
+            let next_file = &next_file;
+            scope.spawn(move || {
+                while let Some((idx, file)) = files.get(next_file.fetch_add(1, Ordering::Relaxed)) {
+                    let _ = sender.send((*idx, file, GameFileInfo::hash_file(file)));
+                }
+            });
+        }
+        drop(sender);
+        receiver
+            .iter()
+            .map(|(idx, file, hash)| {
+                progress.hash_file(file);
+                (idx, hash)
+            })
+            .collect()
+    })
+}
+
+/// A game name has to be a single folder name, otherwise it could point anywhere in the data folder.
+pub fn is_folder_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    !name.contains('\\')
+        && matches!(components.next(), Some(std::path::Component::Normal(_)))
+        && components.next().is_none()
+}
+
+pub fn remove_game_infos(data_folder: &Path, game: &str) {
+    for path in [
+        get_game_infos_path(data_folder, game),
+        get_csv_path(data_folder, game),
+    ] {
+        if path.is_file() {
+            match std::fs::remove_file(&path) {
+                Ok(_) => info!("Removed: {}", path.display()),
+                Err(err) => warn!("Failed to remove: {} Error: {err}", path.display()),
+            }
+        }
+    }
 }
 
 pub fn save_infos_to_data_folder(data_folder: &Path, game_infos: &GameInfos) {
@@ -267,3 +387,112 @@ pub fn glob_for_possible_exe(root: &Path) -> Option<String> {
     }
     None
 }
+
+/// The bundled emulator of a GOG game, in the game root or in a folder of it.
+pub fn find_emulator_exe(game_root: &Path, exe_name: &str) -> Option<String> {
+    let mut exes: Vec<PathBuf> = WalkDir::new(game_root)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.file_type().is_file() && entry.file_name().eq_ignore_ascii_case(exe_name)
+        })
+        .map(|entry| entry.into_path())
+        .collect();
+    exes.sort();
+    let exe = exes.first()?.strip_prefix(game_root).ok()?;
+    Some(exe.to_str()?.replace('\\', "/"))
+}
+
+/// The script of GOG linux packages, which runs the bundled emulator.
+pub fn get_gog_start_script(game_root: &Path) -> Option<String> {
+    const START_SCRIPT: &str = "start.sh";
+    game_root
+        .join(START_SCRIPT)
+        .is_file()
+        .then(|| START_SCRIPT.to_string())
+}
+
+/// The name in the goggame-<id>.info file of GOG games.
+pub fn get_gog_game_name(game_root: &Path) -> Option<String> {
+    let info_file = glob_file_path(game_root, "goggame-*.info")?;
+    let info: serde_json::Value =
+        serde_json::from_reader(std::fs::File::open(info_file).ok()?).ok()?;
+    let name = info.get("name")?.as_str()?.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::configuration::Configuration;
+    use crate::utils::{read_existing_file_infos, save_game_file_infos};
+    use minus_games_models::game_file_info::GameFileInfo;
+    use minus_games_models::game_infos::GameInfos;
+    use minus_games_utils::{get_csv_path, set_file_modified_time};
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    fn create_config(temp_dir: &Path) -> Configuration {
+        Configuration {
+            games_folder: temp_dir.join("games"),
+            libraries: Vec::new(),
+            data_folder: temp_dir.join("data"),
+            cache_folder: None,
+            keep_existing_configs: false,
+            cleanup_data_folder: false,
+            filter: None,
+            watch: false,
+            config_file: None,
+        }
+    }
+
+    #[test]
+    fn test_save_game_file_infos_reuses_unchanged_hashes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = create_config(temp_dir.path());
+        let game_folder = config.games_folder.join("Game");
+        std::fs::create_dir_all(&game_folder).unwrap();
+        let last_modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        for file in ["unchanged.dat", "changed.dat"] {
+            std::fs::write(game_folder.join(file), "old").unwrap();
+            set_file_modified_time(&game_folder.join(file), last_modified);
+        }
+        let game_infos = GameInfos {
+            folder_name: "Game".to_string(),
+            ..Default::default()
+        };
+
+        let hash_count = save_game_file_infos(
+            &game_folder,
+            &config.games_folder,
+            &config,
+            &game_infos,
+            &mut (),
+        );
+        assert_eq!((hash_count.reused, hash_count.hashed), (0, 2));
+
+        // Same size, only the modified time tells the change apart
+        let changed_file = game_folder.join("changed.dat");
+        std::fs::write(&changed_file, "new").unwrap();
+        set_file_modified_time(&changed_file, last_modified + Duration::from_secs(60));
+
+        let hash_count = save_game_file_infos(
+            &game_folder,
+            &config.games_folder,
+            &config,
+            &game_infos,
+            &mut (),
+        );
+        assert_eq!((hash_count.reused, hash_count.hashed), (1, 1));
+
+        let file_infos = read_existing_file_infos(&get_csv_path(&config.data_folder, "Game"));
+        assert_eq!(
+            file_infos["Game/changed.dat"].hash,
+            GameFileInfo::hash_file(&changed_file)
+        );
+        assert_eq!(
+            file_infos["Game/unchanged.dat"].hash,
+            GameFileInfo::hash_file(&game_folder.join("unchanged.dat"))
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use crate::configuration::Library;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::Component;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Copying a game creates many events, wait until the folder is quiet.
+const DEBOUNCE: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Blocks and calls `on_game_changed` with the folder name of every game,
+/// which was added, changed or removed in one of the libraries.
+/// It is called with `None`, if events were lost, e.g. on a queue overflow, and every game has to be checked.
+pub fn watch_libraries(
+    libraries: &[Library],
+    mut on_game_changed: impl FnMut(Option<&str>),
+) -> notify::Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    for library in libraries {
+        match watcher.watch(&library.path, RecursiveMode::Recursive) {
+            Ok(_) => info!(
+                "Watching library {}: {}",
+                library.name,
+                library.path.display()
+            ),
+            Err(err) => warn!("Failed to watch library {}. Error: {err}", library.name),
+        }
+    }
+
+    let mut pending_games: HashMap<String, Instant> = HashMap::new();
+    let mut pending_rescan: Option<Instant> = None;
+    loop {
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(Ok(event)) if event.need_rescan() => {
+                warn!("Changes in the libraries were lost, all games will be checked");
+                pending_rescan = Some(Instant::now());
+            }
+            Ok(Ok(event)) => {
+                for game in get_changed_games(libraries, &event) {
+                    pending_games.insert(game, Instant::now());
+                }
+            }
+            Ok(Err(err)) => {
+                warn!("Watch error: {err}, all games will be checked");
+                pending_rescan = Some(Instant::now());
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+
+        if let Some(last_event) = pending_rescan {
+            // Also covers the pending games
+            if last_event.elapsed() >= DEBOUNCE {
+                pending_rescan = None;
+                pending_games.clear();
+                on_game_changed(None);
+            }
+            continue;
+        }
+
+        let quiet_games: Vec<String> = pending_games
+            .iter()
+            .filter(|(_, last_event)| last_event.elapsed() >= DEBOUNCE)
+            .map(|(game, _)| game.clone())
+            .collect();
+        for game in quiet_games {
+            pending_games.remove(&game);
+            on_game_changed(Some(&game));
+        }
+    }
+}
+
+fn get_changed_games(libraries: &[Library], event: &Event) -> Vec<String> {
+    // Reading files, e.g. downloads, doesn't change a game
+    if matches!(event.kind, EventKind::Access(_)) {
+        return Vec::new();
+    }
+    let mut games = Vec::new();
+    for path in event.paths.iter() {
+        for library in libraries {
+            if let Ok(relative_path) = path.strip_prefix(&library.path)
+                && let Some(Component::Normal(game)) = relative_path.components().next()
+                && let Some(game) = game.to_str()
+            {
+                games.push(game.to_string());
+            }
+        }
+    }
+    games
+}
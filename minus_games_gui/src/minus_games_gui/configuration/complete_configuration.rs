@@ -21,6 +21,12 @@ pub struct CompleteConfiguration {
     pub wine_exe: Option<PathBuf>,
     #[arg(long, env = "MINUS_GAMES_WINE_PREFIX")]
     pub wine_prefix: Option<PathBuf>,
+    #[arg(long, env = "MINUS_GAMES_MKXP_Z")]
+    pub mkxp_z: Option<PathBuf>,
+    #[arg(long, env = "MINUS_GAMES_DOSBOX")]
+    pub dosbox: Option<PathBuf>,
+    #[arg(long, env = "MINUS_GAMES_SCUMMVM")]
+    pub scummvm: Option<PathBuf>,
     #[arg(short, long, default_value = "false", env = "MINUS_GAMES_VERBOSE")]
     pub verbose: bool,
     #[arg(short, long, default_value = "false", env = "MINUS_GAMES_OFFLINE")]
@@ -33,10 +39,18 @@ pub struct CompleteConfiguration {
     pub username: Option<String>,
     #[arg(long, env = "MINUS_GAMES_PASSWORD")]
     pub password: Option<String>,
+    #[arg(long, env = "MINUS_GAMES_TOKEN")]
+    pub token: Option<String>,
+    #[arg(long, env = "MINUS_GAMES_CA_BUNDLE")]
+    pub ca_bundle: Option<PathBuf>,
+    #[arg(long, env = "MINUS_GAMES_CERT_FINGERPRINT")]
+    pub cert_fingerprint: Option<String>,
     #[arg(long, default_value = "false", env = "MINUS_GAMES_NO_GAMEMODERUN")]
     pub no_gamemoderun: bool,
     #[arg(long, default_value = "true", env = "MINUS_GAMES_SYNC")]
     pub sync: bool,
+    #[arg(long, default_value = "3", env = "MINUS_GAMES_DOWNLOAD_RETRIES")]
+    pub download_retries: usize,
     #[command(subcommand)]
     pub action: Option<ClientActions>,
     #[arg(long, env = "MINUS_GAMES_GUI_FULLSCREEN")]
@@ -72,14 +86,21 @@ impl CompleteConfiguration {
             client_folder: self.client_folder,
             wine_exe: self.wine_exe,
             wine_prefix: self.wine_prefix,
+            mkxp_z: self.mkxp_z,
+            dosbox: self.dosbox,
+            scummvm: self.scummvm,
             verbose: self.verbose,
             offline: self.offline,
             client_games_folder: self.client_games_folder,
             client_cache_folder: self.client_cache_folder,
             username: self.username,
             password: self.password,
+            token: self.token,
+            ca_bundle: self.ca_bundle,
+            cert_fingerprint: self.cert_fingerprint,
             no_gamemoderun: self.no_gamemoderun,
             sync: self.sync,
+            download_retries: self.download_retries,
             action: self.action,
         };
 
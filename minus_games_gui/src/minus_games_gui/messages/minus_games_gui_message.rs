@@ -66,6 +66,7 @@ pub(crate) enum MinusGamesGuiMessage {
     LazyImageDownloaderReady(Sender<(String, bool, usize)>),
     LazyImageUpdateCard(usize, iced::widget::image::Handle),
     FinishedProcessingImages(()),
+    TokenCreated(Option<String>),
 }
 
 impl From<MinusGamesClientEvents> for MinusGamesGuiMessage {
@@ -78,6 +79,12 @@ impl From<MinusGamesClientEvents> for MinusGamesGuiMessage {
             MinusGamesClientEvents::FinishedDownloadingFile => {
                 MinusGamesGuiMessage::FinishedDownloading
             }
+            MinusGamesClientEvents::FailedDownloadingFile(file) => {
+                MinusGamesGuiMessage::LogMessage(format!("Failed to download: {file}"))
+            }
+            MinusGamesClientEvents::SaveConflict(game, file_path) => {
+                MinusGamesGuiMessage::LogMessage(format!("Save conflict in {game}: {file_path}"))
+            }
             MinusGamesClientEvents::FinishedDownloadingFiles => MinusGamesGuiMessage::Noop,
             MinusGamesClientEvents::FinishedSyncFileInfos => {
                 MinusGamesGuiMessage::SyncFileInfosComplete
@@ -10,12 +10,18 @@ pub(crate) struct MinusGamesSettings {
     pub client_games_folder: String,
     pub wine_exe: String,
     pub wine_prefix: String,
+    pub mkxp_z: String,
+    pub dosbox: String,
+    pub scummvm: String,
     pub verbose: bool,
     pub offline: bool,
     pub sync: bool,
     pub fullscreen: bool,
     pub username: String,
     pub password: String,
+    pub token: String,
+    pub ca_bundle: String,
+    pub cert_fingerprint: String,
     pub theme: Option<Theme>,
     pub initial_theme: Option<Theme>,
     pub scale: f32,
@@ -52,12 +58,30 @@ impl MinusGamesSettings {
                 None => "".to_string(),
                 Some(val) => val.to_str().unwrap().to_string(),
             },
+            mkxp_z: match value.mkxp_z.as_ref() {
+                None => "".to_string(),
+                Some(val) => val.to_str().unwrap().to_string(),
+            },
+            dosbox: match value.dosbox.as_ref() {
+                None => "".to_string(),
+                Some(val) => val.to_str().unwrap().to_string(),
+            },
+            scummvm: match value.scummvm.as_ref() {
+                None => "".to_string(),
+                Some(val) => val.to_str().unwrap().to_string(),
+            },
             verbose: value.verbose,
             offline: value.offline,
             sync: value.sync,
             fullscreen: get_gui_config().fullscreen,
             username: value.username.clone().unwrap_or_default(),
             password: value.password.clone().unwrap_or_default(),
+            token: value.token.clone().unwrap_or_default(),
+            ca_bundle: match value.ca_bundle.as_ref() {
+                None => "".to_string(),
+                Some(val) => val.to_str().unwrap().to_string(),
+            },
+            cert_fingerprint: value.cert_fingerprint.clone().unwrap_or_default(),
             initial_theme: theme.clone(),
             theme,
             scale: value_gui.scale.unwrap_or(1.0),
@@ -9,7 +9,7 @@ use crate::minus_games_gui::messages::minus_games_gui_message::MinusGamesGuiMess
 use crate::minus_games_gui::messages::modal_callback::ModalCallback;
 use crate::minus_games_gui::minus_games_settings::MinusGamesSettings;
 use crate::minus_games_gui::settings::{
-    handle_change_event, override_gui_config, save_new_settings,
+    create_token, handle_change_event, override_gui_config, save_new_settings,
 };
 use crate::minus_games_gui::style_constants::{
     GAME_CARD_ROW_HEIGHT, HALF_MARGIN_DEFAULT, MARGIN_DEFAULT, SPACING_DEFAULT, TEXT,
@@ -47,7 +47,7 @@ use minus_games_models::game_infos::{GameInfos, MinimalGameInfos};
 use settings::override_config;
 use std::cmp;
 use std::sync::atomic::Ordering::Relaxed;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 pub mod configuration;
 mod game_card;
@@ -568,24 +568,34 @@ impl MinusGamesGui {
                 }
             }
             MinusGamesGuiMessage::BackFromSettings(save) => {
-                if save {
-                    save_new_settings(self.settings.as_ref());
-                    override_config(self.settings.as_ref());
-                    override_gui_config(self.settings.as_ref());
-                    if let Some(settings) = self.settings.take() {
-                        self.theme = settings.theme;
-                        self.scale = Some(settings.scale);
+                if save
+                    && let Some(settings) = self.settings.as_ref()
+                    && !settings.offline
+                    && !settings.username.is_empty()
+                    && !settings.password.is_empty()
+                {
+                    return Task::perform(
+                        create_token(settings.clone()),
+                        MinusGamesGuiMessage::TokenCreated,
+                    );
+                }
+                return self.leave_settings(save);
+            }
+            MinusGamesGuiMessage::TokenCreated(token_option) => {
+                if let Some(settings) = self.settings.as_mut() {
+                    match token_option {
+                        Some(token) => {
+                            settings.token = token;
+                            settings.password.clear();
+                        }
+                        None => {
+                            warn!(
+                                "Failed to create a token, the password is only used for this session"
+                            )
+                        }
                     }
-                    reset_client();
-                } else if let Some(settings) = self.settings.take() {
-                    self.theme = settings.initial_theme;
                 }
-                self.state = MinusGamesState::Loading;
-                return Task::batch([
-                    Task::done(MinusGamesGuiMessage::UpdateSystemTheme(())),
-                    Task::done(MinusGamesGuiMessage::ApplyScreenSettings),
-                    Self::load(),
-                ]);
+                return self.leave_settings(true);
             }
             MinusGamesGuiMessage::ChangeSetting(change_input) => {
                 handle_change_event(self.settings.as_mut(), change_input);
@@ -815,6 +825,27 @@ impl MinusGamesGui {
         }
     }
 
+    fn leave_settings(&mut self, save: bool) -> Task<MinusGamesGuiMessage> {
+        if save {
+            save_new_settings(self.settings.as_ref());
+            override_config(self.settings.as_ref());
+            override_gui_config(self.settings.as_ref());
+            if let Some(settings) = self.settings.take() {
+                self.theme = settings.theme;
+                self.scale = Some(settings.scale);
+            }
+            reset_client();
+        } else if let Some(settings) = self.settings.take() {
+            self.theme = settings.initial_theme;
+        }
+        self.state = MinusGamesState::Loading;
+        Task::batch([
+            Task::done(MinusGamesGuiMessage::UpdateSystemTheme(())),
+            Task::done(MinusGamesGuiMessage::ApplyScreenSettings),
+            Self::load(),
+        ])
+    }
+
     fn affirmative_modal_action(&mut self) -> Task<MinusGamesGuiMessage> {
         if self.modal.is_some()
             && let Some((current_game_name, _)) = self.modal.as_ref()
@@ -1,6 +1,8 @@
 use crate::minus_games_gui::minus_games_settings::MinusGamesSettings;
 use crate::minus_games_gui::views::settings_view::SettingInput;
 use crate::runtime::get_mut_gui_config;
+use minus_games_client::actions::auth::get_device_name;
+use minus_games_client::minus_games_client::MinusGamesClient;
 use minus_games_client::runtime::{OFFLINE, get_mut_config};
 use std::{
     io::{BufWriter, Write},
@@ -17,6 +19,9 @@ pub(crate) fn override_config(minus_games_settings_option: Option<&MinusGamesSet
             PathBuf::from_str(&minus_games_settings.client_folder).unwrap_or_default();
         get_mut_config().wine_exe = resolve_path(&minus_games_settings.wine_exe);
         get_mut_config().wine_prefix = resolve_path(&minus_games_settings.wine_prefix);
+        get_mut_config().mkxp_z = resolve_path(&minus_games_settings.mkxp_z);
+        get_mut_config().dosbox = resolve_path(&minus_games_settings.dosbox);
+        get_mut_config().scummvm = resolve_path(&minus_games_settings.scummvm);
         get_mut_config().verbose = minus_games_settings.verbose;
         get_mut_config().offline = minus_games_settings.offline;
         OFFLINE.store(minus_games_settings.offline, Ordering::Relaxed);
@@ -24,6 +29,9 @@ pub(crate) fn override_config(minus_games_settings_option: Option<&MinusGamesSet
             PathBuf::from_str(&minus_games_settings.client_games_folder).unwrap_or_default();
         get_mut_config().username = resolve_string(&minus_games_settings.username);
         get_mut_config().password = resolve_string(&minus_games_settings.password);
+        get_mut_config().token = resolve_string(&minus_games_settings.token);
+        get_mut_config().ca_bundle = resolve_path(&minus_games_settings.ca_bundle);
+        get_mut_config().cert_fingerprint = resolve_string(&minus_games_settings.cert_fingerprint);
         get_mut_gui_config().fullscreen = minus_games_settings.fullscreen;
         get_mut_gui_config().theme = minus_games_settings.get_optinal_theme_name();
     }
@@ -35,6 +43,20 @@ pub(crate) fn override_gui_config(minus_games_settings_option: Option<&MinusGame
     }
 }
 
+pub(crate) async fn create_token(settings: MinusGamesSettings) -> Option<String> {
+    MinusGamesClient::new(
+        &settings.server_url,
+        None,
+        None,
+        None,
+        resolve_path(&settings.ca_bundle).as_ref(),
+        resolve_string(&settings.cert_fingerprint).as_ref(),
+    )
+    .create_token(&settings.username, &settings.password, &get_device_name())
+    .await
+    .map(|auth_token| auth_token.token)
+}
+
 fn resolve_path(value: &str) -> Option<PathBuf> {
     if value.is_empty() {
         None
@@ -60,6 +82,12 @@ pub(crate) fn handle_change_event(
             SettingInput::ServerUrl(change) => {
                 minus_games_settings.server_url = change.trim().to_string();
             }
+            SettingInput::CaBundle(change) => {
+                minus_games_settings.ca_bundle = change.trim().to_string();
+            }
+            SettingInput::CertFingerprint(change) => {
+                minus_games_settings.cert_fingerprint = change.trim().to_string();
+            }
             SettingInput::ClientFolder(change) => {
                 minus_games_settings.client_folder = change.trim().to_string();
             }
@@ -74,6 +102,18 @@ pub(crate) fn handle_change_event(
             SettingInput::WinePrefix(change) => {
                 minus_games_settings.wine_prefix = change.trim().to_string();
             }
+            #[cfg(not(target_family = "windows"))]
+            SettingInput::MkxpZ(change) => {
+                minus_games_settings.mkxp_z = change.trim().to_string();
+            }
+            #[cfg(not(target_family = "windows"))]
+            SettingInput::DOSBox(change) => {
+                minus_games_settings.dosbox = change.trim().to_string();
+            }
+            #[cfg(not(target_family = "windows"))]
+            SettingInput::ScummVM(change) => {
+                minus_games_settings.scummvm = change.trim().to_string();
+            }
             SettingInput::Verbose(change) => {
                 minus_games_settings.verbose = change;
             }
@@ -198,6 +238,42 @@ pub(crate) fn save_new_settings(settings_option: Option<&MinusGamesSettings>) {
                         )
                         .unwrap();
                 }
+                if !settings.mkxp_z.trim().is_empty() {
+                    writer
+                        .write_all(
+                            format!(
+                                "MINUS_GAMES_MKXP_Z=\"{}\"{}",
+                                settings.mkxp_z.trim(),
+                                NEW_LINE
+                            )
+                            .as_bytes(),
+                        )
+                        .unwrap();
+                }
+                if !settings.dosbox.trim().is_empty() {
+                    writer
+                        .write_all(
+                            format!(
+                                "MINUS_GAMES_DOSBOX=\"{}\"{}",
+                                settings.dosbox.trim(),
+                                NEW_LINE
+                            )
+                            .as_bytes(),
+                        )
+                        .unwrap();
+                }
+                if !settings.scummvm.trim().is_empty() {
+                    writer
+                        .write_all(
+                            format!(
+                                "MINUS_GAMES_SCUMMVM=\"{}\"{}",
+                                settings.scummvm.trim(),
+                                NEW_LINE
+                            )
+                            .as_bytes(),
+                        )
+                        .unwrap();
+                }
                 writer
                     .write_all(
                         format!("MINUS_GAMES_VERBOSE=\"{}\"{}", settings.verbose, NEW_LINE)
@@ -236,12 +312,36 @@ pub(crate) fn save_new_settings(settings_option: Option<&MinusGamesSettings>) {
                         )
                         .unwrap();
                 }
-                if !settings.password.trim().is_empty() {
+                if !settings.token.trim().is_empty() {
+                    writer
+                        .write_all(
+                            format!(
+                                "MINUS_GAMES_TOKEN=\"{}\"{}",
+                                settings.token.trim(),
+                                NEW_LINE
+                            )
+                            .as_bytes(),
+                        )
+                        .unwrap();
+                }
+                if !settings.ca_bundle.trim().is_empty() {
+                    writer
+                        .write_all(
+                            format!(
+                                "MINUS_GAMES_CA_BUNDLE=\"{}\"{}",
+                                settings.ca_bundle.trim().replace("\\", "\\\\"),
+                                NEW_LINE
+                            )
+                            .as_bytes(),
+                        )
+                        .unwrap();
+                }
+                if !settings.cert_fingerprint.trim().is_empty() {
                     writer
                         .write_all(
                             format!(
-                                "MINUS_GAMES_PASSWORD=\"{}\"{}",
-                                settings.password.trim(),
+                                "MINUS_GAMES_CERT_FINGERPRINT=\"{}\"{}",
+                                settings.cert_fingerprint.trim(),
                                 NEW_LINE
                             )
                             .as_bytes(),
@@ -50,12 +50,20 @@ static FONT_FAMILIES: FontFamily = FontFamily(LazyLock::new(|| {
 #[derive(Clone, Debug)]
 pub enum SettingInput {
     ServerUrl(String),
+    CaBundle(String),
+    CertFingerprint(String),
     ClientFolder(String),
     ClientGamesFolder(String),
     #[cfg(not(target_family = "windows"))]
     WineExe(String),
     #[cfg(not(target_family = "windows"))]
     WinePrefix(String),
+    #[cfg(not(target_family = "windows"))]
+    MkxpZ(String),
+    #[cfg(not(target_family = "windows"))]
+    DOSBox(String),
+    #[cfg(not(target_family = "windows"))]
+    ScummVM(String),
     Verbose(bool),
     Offline(bool),
     Sync(bool),
@@ -92,7 +100,7 @@ macro_rules! add_checkbox_input {
 }
 
 pub(crate) fn view(minus_games_gui: &MinusGamesGui) -> Row<'_, MinusGamesGuiMessage> {
-    let mut settings = Column::with_capacity(3 * 9 + 4);
+    let mut settings = Column::with_capacity(3 * 14 + 4);
     settings = add_setting_input!(minus_games_gui, settings, "Username", username, Username);
     settings = add_setting_input!(minus_games_gui, settings, "Password", password, Password);
     settings = add_setting_input!(
@@ -102,6 +110,14 @@ pub(crate) fn view(minus_games_gui: &MinusGamesGui) -> Row<'_, MinusGamesGuiMess
         server_url,
         ServerUrl
     );
+    settings = add_setting_input!(minus_games_gui, settings, "CA Bundle", ca_bundle, CaBundle);
+    settings = add_setting_input!(
+        minus_games_gui,
+        settings,
+        "Certificate Fingerprint",
+        cert_fingerprint,
+        CertFingerprint
+    );
     settings = add_setting_input!(
         minus_games_gui,
         settings,
@@ -126,6 +142,9 @@ pub(crate) fn view(minus_games_gui: &MinusGamesGui) -> Row<'_, MinusGamesGuiMess
             wine_prefix,
             WinePrefix
         );
+        settings = add_setting_input!(minus_games_gui, settings, "mkxp-z", mkxp_z, MkxpZ);
+        settings = add_setting_input!(minus_games_gui, settings, "DOSBox", dosbox, DOSBox);
+        settings = add_setting_input!(minus_games_gui, settings, "ScummVM", scummvm, ScummVM);
     }
     let row = Row::with_capacity(5)
         .push(add_checkbox_input!(
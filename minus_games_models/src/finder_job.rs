@@ -0,0 +1,52 @@
+use crate::GameEngine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum FinderJobState {
+    Queued,
+    Running,
+    Finished,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum FinderGameStatus {
+    Updated,
+    Unchanged,
+    Failed,
+    Skipped,
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct FinderGameResult {
+    pub game: String,
+    pub status: FinderGameStatus,
+    pub engine: Option<GameEngine>,
+    pub reason: Option<String>,
+    /// Files whose hash was taken over from the previous run
+    #[serde(default)]
+    pub reused_hashes: usize,
+    #[serde(default)]
+    pub hashed_files: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct FinderJob {
+    pub id: String,
+    pub description: String,
+    pub state: FinderJobState,
+    pub queued: DateTime<Utc>,
+    pub started: Option<DateTime<Utc>>,
+    pub finished: Option<DateTime<Utc>>,
+    pub total: usize,
+    pub processed: usize,
+    pub current_game: Option<String>,
+    pub current_file: Option<String>,
+    pub error: Option<String>,
+    pub results: Vec<FinderGameResult>,
+}
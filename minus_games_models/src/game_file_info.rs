@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::fs::File;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -32,6 +32,13 @@ impl GameFileInfo {
 
 impl GameFileInfo {
     pub fn from_path_buf_with_cut_off(file: PathBuf, cut_off: usize) -> GameFileInfo {
+        let mut game_file_info = Self::from_path_without_hash(&file, cut_off);
+        game_file_info.hash = Self::hash_file(&file);
+        game_file_info
+    }
+
+    /// Reads everything except the hash, which is left empty.
+    pub fn from_path_without_hash(file: &Path, cut_off: usize) -> GameFileInfo {
         let file_name = file.file_name().unwrap().to_str().unwrap().to_string();
         #[cfg(target_family = "unix")]
         let file_path = file
@@ -52,22 +59,31 @@ impl GameFileInfo {
         let metadata = file.metadata().unwrap();
         let size = metadata.len();
         let last_modified = metadata.modified().unwrap();
-        let file = File::open(file.as_path()).unwrap();
-
-        let hash = blake3::Hasher::new()
-            .update_reader(file)
-            .unwrap()
-            .finalize()
-            .to_string();
 
         GameFileInfo {
             file_name,
             file_path,
             size,
             last_modified: last_modified.into(),
-            hash,
+            hash: String::new(),
         }
     }
+
+    pub fn hash_file(file: &Path) -> String {
+        let file = File::open(file).unwrap();
+        blake3::Hasher::new()
+            .update_reader(file)
+            .unwrap()
+            .finalize()
+            .to_string()
+    }
+
+    /// Same path, size and modification time, so the hash can be reused.
+    pub fn is_unchanged(&self, other: &GameFileInfo) -> bool {
+        self.file_path == other.file_path
+            && self.size == other.size
+            && self.last_modified == other.last_modified
+    }
 }
 
 impl Display for GameFileInfo {
@@ -1,4 +1,5 @@
 use crate::{GameEngine, SupportedPlatforms};
+use glob::{MatchOptions, Pattern};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
@@ -15,9 +16,27 @@ pub struct GameInfos {
     pub windows_exe: Option<String>,
     pub sync_folders: Option<Vec<String>>,
     pub excludes: Option<Vec<String>>,
+    /// Only files matching one of the glob patterns are synced, e.g. saves in the game root
+    pub includes: Option<Vec<String>>,
+    /// The emulator runs the game instead of an executable, e.g. DOSBox or ScummVM
+    pub emulator_config: Option<EmulatorConfig>,
+    pub tags: Option<Vec<String>>,
+    pub library: Option<String>,
     // pub additions: Option<Additions>,
 }
 
+/// Paths are relative to the game root.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, ToSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct EmulatorConfig {
+    /// Passed to the emulator in this order
+    pub config_files: Vec<String>,
+    /// The emulator runs in this folder, relative paths in the config files start there
+    pub working_folder: Option<String>,
+    /// The ScummVM target in the config file
+    pub target: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug, Clone, ToSchema)]
 #[serde(rename_all = "PascalCase")]
 pub struct MinimalGameInfos {
@@ -70,6 +89,26 @@ impl GameInfos {
         false
     }
 
+    pub fn is_included(&self, file_path: &str) -> bool {
+        let Some(includes) = self.includes.as_ref() else {
+            return true;
+        };
+        let options = MatchOptions {
+            case_sensitive: false,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        includes.iter().any(|include| {
+            Pattern::new(include).is_ok_and(|pattern| pattern.matches_with(file_path, options))
+        })
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags
+            .as_ref()
+            .is_some_and(|tags| tags.iter().any(|i| i.eq_ignore_ascii_case(tag)))
+    }
+
     pub fn get_linux_exe(&self, game_folder: &Path) -> Option<PathBuf> {
         std::path::absolute(
             game_folder
@@ -78,6 +117,15 @@ impl GameInfos {
         )
         .ok()
     }
+    pub fn get_emulator_working_folder(&self, game_folder: &Path) -> Option<PathBuf> {
+        let game_root = game_folder.join(self.folder_name.as_str());
+        let emulator_config = self.emulator_config.as_ref()?;
+        std::path::absolute(match emulator_config.working_folder.as_ref() {
+            Some(working_folder) => game_root.join(working_folder),
+            None => game_root,
+        })
+        .ok()
+    }
     pub fn get_windows_exe(&self, game_folder: &Path) -> Option<PathBuf> {
         std::path::absolute(
             game_folder
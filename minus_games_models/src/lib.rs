@@ -1,8 +1,13 @@
+pub mod auth_token;
+pub mod finder_job;
 pub mod game_file_info;
 pub mod game_infos;
 pub mod game_list;
+pub mod library_conflict;
 pub mod other;
 pub mod rpgm_package;
+pub mod session_info;
+pub mod sync_file_history;
 pub mod sync_file_info;
 
 use serde::{Deserialize, Serialize};
@@ -15,12 +20,17 @@ use utoipa::ToSchema;
 pub enum GameEngine {
     RenPy,
     RPGMaker,
+    RPGMakerRGSS,
     Unreal,
     Unity,
     UnityOld,
     WolfRPGEditor,
     Kirikiri,
     Electron,
+    Godot,
+    GameMaker,
+    DOSBox,
+    ScummVM,
     #[default]
     Other,
 }
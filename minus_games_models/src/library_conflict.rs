@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A game folder which exists in more than one library.
+/// The first library wins, the others are ignored.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct LibraryConflict {
+    pub game: String,
+    pub libraries: Vec<String>,
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct SyncFileRevision {
+    pub revision: String,
+    pub size: u64,
+    pub last_modified: DateTime<Utc>,
+}
+
+impl Display for SyncFileRevision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Revision: {}, Size: {}, Last Modified: {}",
+            self.revision, self.size, self.last_modified
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct SyncFileHistory {
+    pub file_path: String,
+    pub revisions: Vec<SyncFileRevision>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct RestoreSyncFile {
+    pub file_path: String,
+    pub revision: String,
+}
@@ -0,0 +1,146 @@
+use crate::auth::user::{Group, NewUser, UpdateUser, UserInfo};
+use crate::auth::user_handler::{UserError, UserHandler};
+use crate::configuration::{GroupActions, ServerActions, UserActions};
+use tracing::{info, warn};
+
+/// Runs an offline administration action and returns if it was successful.
+pub fn run_action(user_handler: &UserHandler, action: ServerActions) -> bool {
+    match action {
+        ServerActions::User(user_action) => run_user_action(user_handler, user_action),
+        ServerActions::Group(group_action) => run_group_action(user_handler, group_action),
+    }
+}
+
+fn run_group_action(user_handler: &UserHandler, action: GroupActions) -> bool {
+    let result = match action {
+        GroupActions::List => {
+            for group in user_handler.read_groups() {
+                info!(
+                    "{}: roles {:?}, include_list {:?}, exclude_list {:?}",
+                    group.name, group.roles, group.include_list, group.exclude_list
+                );
+            }
+            Ok(())
+        }
+        GroupActions::Set {
+            name,
+            role,
+            include,
+            exclude,
+        } => {
+            let group = Group {
+                name,
+                include_list: include,
+                exclude_list: exclude,
+                roles: role,
+            };
+            user_handler
+                .save_group(&group)
+                .map(|_| info!("Saved group {}", group.name))
+        }
+        GroupActions::Delete { name } => user_handler
+            .delete_group(&name)
+            .map(|_| info!("Deleted group {name}")),
+    };
+
+    if let Err(err) = result {
+        warn!("{err}");
+        return false;
+    }
+    true
+}
+
+fn run_user_action(user_handler: &UserHandler, action: UserActions) -> bool {
+    let result = match action {
+        UserActions::List => {
+            for (username, user) in user_handler.read_users() {
+                match user {
+                    Ok(user) => {
+                        let user_info = UserInfo::from(&user);
+                        info!(
+                            "{username}: roles {:?}, groups {:?}, sync {}, disabled {}, include_list {:?}, exclude_list {:?}",
+                            user_info.roles,
+                            user_info.groups,
+                            user_info.sync,
+                            user_info.disabled,
+                            user_info.include_list,
+                            user_info.exclude_list
+                        );
+                    }
+                    Err(err) => warn!("{username}: {err}"),
+                }
+            }
+            Ok(())
+        }
+        UserActions::Check => {
+            if user_handler.check_users() {
+                info!("All user files are valid");
+                Ok(())
+            } else {
+                return false;
+            }
+        }
+        UserActions::Add {
+            username,
+            password,
+            role,
+            group,
+            sync,
+            include,
+            exclude,
+        } => user_handler
+            .create_user(NewUser {
+                username,
+                password: password.unwrap_or_else(read_password),
+                include_list: include,
+                exclude_list: exclude,
+                groups: group,
+                roles: role,
+                sync,
+            })
+            .map(|user| info!("Created user {}", user.username)),
+        UserActions::SetPassword { username, password } => user_handler
+            .update_user(
+                &username,
+                UpdateUser {
+                    password: Some(password.unwrap_or_else(read_password)),
+                    ..UpdateUser::default()
+                },
+            )
+            .map(|_| info!("Changed password of {username}")),
+        UserActions::Disable { username } => set_disabled(user_handler, username, true),
+        UserActions::Enable { username } => set_disabled(user_handler, username, false),
+        UserActions::Delete { username } => user_handler
+            .delete_user(&username)
+            .map(|_| info!("Deleted user {username}")),
+    };
+
+    if let Err(err) = result {
+        warn!("{err}");
+        return false;
+    }
+    true
+}
+
+fn set_disabled(
+    user_handler: &UserHandler,
+    username: String,
+    disabled: bool,
+) -> Result<(), UserError> {
+    user_handler
+        .update_user(
+            &username,
+            UpdateUser {
+                disabled: Some(disabled),
+                ..UpdateUser::default()
+            },
+        )
+        .map(|_| info!("User {username} disabled: {disabled}"))
+}
+
+fn read_password() -> String {
+    println!("Password:");
+    let mut password = String::new();
+    std::io::stdin().read_line(&mut password).ok();
+    password.trim_end_matches(['\r', '\n']).to_string()
+}
@@ -1,20 +1,22 @@
 use crate::auth::session_manager::SessionManager;
 use crate::auth::user_handler::UserHandler;
 use crate::configuration::Configuration;
+use crate::controller::sync_controller::write_sync_file;
+use crate::sync_history::{lock_sync_file, sync_parent_folder};
+use crate::utils::safe_join_name;
 use axum::extract::multipart::Field;
+use axum::http::StatusCode;
 use minus_games_models::game_infos::GameInfos;
+use minus_games_utils::constants::INFOS;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
 use tokio::sync::RwLock;
-use tokio::task::JoinHandle;
+use tracing::warn;
 
 pub struct AppState {
     pub config: Configuration,
     pub user_handler: Arc<UserHandler>,
     pub session_manager: Arc<RwLock<SessionManager>>,
-    pub clear_sessions: Arc<RwLock<Option<JoinHandle<()>>>>,
 }
 
 impl AppState {
@@ -24,31 +26,41 @@ impl AppState {
 
     pub fn does_game_exist(&self, game: &str) -> bool {
         let json_name = format!("{game}.json");
-        self.config.data_folder.join(json_name).is_file()
+        safe_join_name(&self.config.data_folder.join(INFOS), &json_name)
+            .is_some_and(|path| path.is_file())
     }
 
-    pub async fn write_save_file(&self, game: &str, mut field: Field<'_>) {
-        let save_folder_path: PathBuf = self.get_save_folder(game);
-        tokio::fs::create_dir_all(save_folder_path.as_path())
+    pub async fn write_save_file(&self, game: &str, field: Field<'_>) -> Result<(), StatusCode> {
+        let save_folder_path: PathBuf =
+            self.get_save_folder(game).ok_or(StatusCode::BAD_REQUEST)?;
+        let save_file_path = field
+            .file_name()
+            .and_then(|file_name| safe_join_name(save_folder_path.as_path(), file_name))
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        let temp_file_path = write_sync_file(save_file_path.as_path(), field, None)
             .await
-            .unwrap();
-        let file_name = field.file_name().unwrap();
-        let save_file_path = save_folder_path.join(file_name);
-        let mut save_file = File::create(save_file_path).await.unwrap();
-        loop {
-            let chunk = field.chunk().await.unwrap();
+            .map_err(|err| {
+                warn!("Rejected upload of {}: {err}", save_file_path.display());
+                StatusCode::BAD_REQUEST
+            })?;
 
-            match chunk {
-                None => break,
-                Some(bytes) => {
-                    let _ = save_file.write(&bytes).await.unwrap();
-                }
-            }
+        let _lock = lock_sync_file(save_file_path.as_path()).await;
+        if let Err(err) =
+            tokio::fs::rename(temp_file_path.as_path(), save_file_path.as_path()).await
+        {
+            warn!(
+                "Failed to store save file {}: {err}",
+                save_file_path.display()
+            );
+            tokio::fs::remove_file(temp_file_path.as_path()).await.ok();
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
         }
+        sync_parent_folder(save_file_path.as_path()).await;
+        Ok(())
     }
 
-    fn get_save_folder(&self, game: &str) -> PathBuf {
+    fn get_save_folder(&self, game: &str) -> Option<PathBuf> {
         const SAVES: &str = "saves";
-        self.config.data_folder.join(SAVES).join(game)
+        safe_join_name(&self.config.data_folder.join(SAVES), game)
     }
 }
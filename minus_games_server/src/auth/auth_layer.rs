@@ -7,9 +7,7 @@ use axum::{extract::Request, response::Response};
 use futures_util::future::BoxFuture;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::Duration;
 use tokio::sync::RwLock;
-use tokio::task::JoinHandle;
 use tower::{Layer, Service};
 use tracing::trace;
 use uuid::Uuid;
@@ -18,19 +16,16 @@ use uuid::Uuid;
 pub struct AuthLayer {
     session_manager: Arc<RwLock<SessionManager>>,
     user_handler: Arc<UserHandler>,
-    clear_sessions: Arc<RwLock<Option<JoinHandle<()>>>>,
 }
 
 impl AuthLayer {
     pub fn new(
         user_handler: Arc<UserHandler>,
         session_manager: Arc<RwLock<SessionManager>>,
-        clear_sessions: Arc<RwLock<Option<JoinHandle<()>>>>,
     ) -> Self {
         Self {
             user_handler,
             session_manager,
-            clear_sessions,
         }
     }
 }
@@ -43,7 +38,6 @@ impl<S> Layer<S> for AuthLayer {
             inner,
             user_handler: self.user_handler.clone(),
             session_manager: self.session_manager.clone(),
-            clear_sessions: self.clear_sessions.clone(),
         }
     }
 }
@@ -53,7 +47,6 @@ pub struct AuthMiddleware<S> {
     inner: S,
     user_handler: Arc<UserHandler>,
     session_manager: Arc<RwLock<SessionManager>>,
-    clear_sessions: Arc<RwLock<Option<JoinHandle<()>>>>,
 }
 
 impl<S> Service<Request> for AuthMiddleware<S>
@@ -79,7 +72,6 @@ where
         let session_manager = self.session_manager.clone();
         let clone = self.inner.clone();
         let mut inner_clone = std::mem::replace(&mut self.inner, clone);
-        let clear_sessions = self.clear_sessions.clone();
 
         // info!("session_id_option: {session_id_option:?}, user_option: {user_option:?}");
         Box::pin(async move {
@@ -89,29 +81,33 @@ where
                     Some(Arc::new(user_handler.get_default_user())),
                 ),
                 (Some(session_id), None) => {
-                    match session_manager.read().await.sessions.get(&session_id) {
+                    match session_manager
+                        .write()
+                        .await
+                        .get_user(&session_id, &user_handler)
+                    {
                         None => (
                             Some(session_id),
                             Some(Arc::new(user_handler.get_default_user())),
                         ),
-                        Some(user) => (None, Some(user.clone())),
+                        Some(user) => (None, Some(user)),
                     }
                 }
                 (None, Some(user_option)) => {
-                    let arc_user = user_handler
-                        .authorize_user_by_username_password(&user_option.0, &user_option.1)
-                        .map(Arc::new);
+                    let arc_user = user_handler.authorize(&user_option).map(Arc::new);
                     (Some(Uuid::new_v4()), arc_user)
                 }
                 (Some(session_id), Some(user_option)) => {
-                    match session_manager.read().await.sessions.get(&session_id) {
+                    match session_manager
+                        .write()
+                        .await
+                        .get_user(&session_id, &user_handler)
+                    {
                         None => {
-                            let arc_user = user_handler
-                                .authorize_user_by_username_password(&user_option.0, &user_option.1)
-                                .map(Arc::new);
+                            let arc_user = user_handler.authorize(&user_option).map(Arc::new);
                             (Some(session_id), arc_user)
                         }
-                        Some(user) => (None, Some(user.clone())),
+                        Some(user) => (None, Some(user)),
                     }
                 }
             };
@@ -131,20 +127,7 @@ where
                     SET_COOKIE,
                     HeaderValue::from_str(&format!("{COOKIES_SESSION_NAME}={id}; path=/")).unwrap(),
                 );
-                session_manager.write().await.sessions.insert(id, user);
-            }
-
-            if let Some(handle) =
-                clear_sessions
-                    .write()
-                    .await
-                    .replace(tokio::task::spawn(async move {
-                        tokio::time::sleep(Duration::from_secs(10 * 60)).await;
-                        trace!("Clear all sessions");
-                        session_manager.write().await.sessions.clear();
-                    }))
-            {
-                handle.abort();
+                session_manager.write().await.insert(id, user);
             }
 
             Ok(response)
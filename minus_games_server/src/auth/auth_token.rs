@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use minus_games_models::auth_token::AuthTokenInfo;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StoredAuthToken {
+    pub id: Uuid,
+    pub username: String,
+    pub device_name: String,
+    pub hash: String,
+    pub created: DateTime<Utc>,
+}
+
+impl StoredAuthToken {
+    /// Creates a new token and returns it together with the plain secret, which is only known to the caller.
+    pub fn new(username: &str, device_name: &str) -> (Self, String) {
+        let id = Uuid::new_v4();
+        let secret = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let token = Self {
+            id,
+            username: username.to_string(),
+            device_name: device_name.to_string(),
+            hash: blake3::hash(secret.as_bytes()).to_hex().to_string(),
+            created: Utc::now(),
+        };
+        (token, format!("{}.{secret}", id.simple()))
+    }
+
+    pub fn verify(&self, secret: &str) -> bool {
+        blake3::Hash::from_hex(&self.hash).is_ok_and(|hash| hash == blake3::hash(secret.as_bytes()))
+    }
+}
+
+impl From<&StoredAuthToken> for AuthTokenInfo {
+    fn from(value: &StoredAuthToken) -> Self {
+        Self {
+            id: value.id.simple().to_string(),
+            device_name: value.device_name.clone(),
+            created: value.created,
+        }
+    }
+}
+
+pub fn split_token(token: &str) -> Option<(Uuid, &str)> {
+    let (id, secret) = token.split_once('.')?;
+    Some((Uuid::from_str(id).ok()?, secret))
+}
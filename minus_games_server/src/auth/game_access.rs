@@ -0,0 +1,47 @@
+use crate::app_state::AppState;
+use crate::auth::user::ArcUser;
+use axum::extract::{FromRequestParts, RawPathParams};
+use axum::http::StatusCode;
+use axum::http::request::Parts;
+use axum::response::{IntoResponse, Response};
+use std::sync::Arc;
+
+/// The `{game}` of a game scoped route, which the current user is allowed to access.
+pub struct AllowedGame(pub String);
+
+impl FromRequestParts<Arc<AppState>> for AllowedGame {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        app_state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let user = ArcUser::from_request_parts(parts, app_state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let path_params = RawPathParams::from_request_parts(parts, app_state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let Some((_, game)) = path_params.iter().find(|(key, _)| *key == "game") else {
+            return Err((StatusCode::BAD_REQUEST, "Missing game").into_response());
+        };
+        check_game_access(&user, game, app_state).map_err(IntoResponse::into_response)?;
+        Ok(AllowedGame(game.to_string()))
+    }
+}
+
+/// Used by [`AllowedGame`] and by the middlewares of the file services,
+/// which don't have path parameters.
+pub fn check_game_access(
+    user: &ArcUser,
+    game: &str,
+    app_state: &AppState,
+) -> Result<(), (StatusCode, String)> {
+    if !user.is_game_allowed(game, &app_state.config) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("Access to '{game}' is not allowed"),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use minus_games_models::game_infos::GameInfos;
+
+const TAG_PREFIX: &str = "tag:";
+const ENGINE_PREFIX: &str = "engine:";
+
+/// An entry of a include or exclude list.
+/// Either a folder name, a glob pattern like `Renpy*` or a `tag:` / `engine:` selector.
+/// A glob still matches the folder name it was written as, like `Game [Deluxe]`.
+pub enum GamePattern {
+    Name(String),
+    Glob(glob::Pattern),
+    Tag(String),
+    Engine(String),
+}
+
+impl GamePattern {
+    pub fn parse(value: &str) -> Self {
+        if let Some(tag) = value.strip_prefix(TAG_PREFIX) {
+            return GamePattern::Tag(tag.to_string());
+        }
+        if let Some(engine) = value.strip_prefix(ENGINE_PREFIX) {
+            return GamePattern::Engine(engine.to_string());
+        }
+        if value.contains(['*', '?', '['])
+            && let Ok(pattern) = glob::Pattern::new(value)
+        {
+            return GamePattern::Glob(pattern);
+        }
+        GamePattern::Name(value.to_string())
+    }
+
+    pub fn needs_game_infos(&self) -> bool {
+        matches!(self, GamePattern::Tag(_) | GamePattern::Engine(_))
+    }
+
+    pub fn matches(&self, game: &str, game_infos: Option<&GameInfos>) -> bool {
+        match self {
+            GamePattern::Name(name) => name == game,
+            GamePattern::Glob(pattern) => pattern.as_str() == game || pattern.matches(game),
+            GamePattern::Tag(tag) => game_infos.is_some_and(|infos| infos.has_tag(tag)),
+            GamePattern::Engine(engine) => game_infos
+                .is_some_and(|infos| infos.engine.to_string().eq_ignore_ascii_case(engine)),
+        }
+    }
+}
+
+pub struct GamePatterns(Vec<GamePattern>);
+
+impl GamePatterns {
+    pub fn parse<'a>(values: impl IntoIterator<Item = &'a String>) -> Self {
+        Self(values.into_iter().map(|i| GamePattern::parse(i)).collect())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn needs_game_infos(&self) -> bool {
+        self.0.iter().any(GamePattern::needs_game_infos)
+    }
+
+    pub fn matches(&self, game: &str, game_infos: Option<&GameInfos>) -> bool {
+        self.0
+            .iter()
+            .any(|pattern| pattern.matches(game, game_infos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::game_pattern::{GamePattern, GamePatterns};
+    use minus_games_models::GameEngine;
+    use minus_games_models::game_infos::GameInfos;
+
+    fn game_infos(engine: GameEngine, tags: &[&str]) -> GameInfos {
+        GameInfos {
+            engine,
+            tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_name_pattern() {
+        let pattern = GamePattern::parse("Celeste");
+        assert!(pattern.matches("Celeste", None));
+        assert!(!pattern.matches("celeste", None));
+        assert!(!pattern.matches("Celeste 2", None));
+        assert!(!pattern.needs_game_infos());
+    }
+
+    #[test]
+    fn test_glob_pattern() {
+        let pattern = GamePattern::parse("Renpy*");
+        assert!(pattern.matches("Renpy", None));
+        assert!(pattern.matches("Renpy Game", None));
+        assert!(!pattern.matches("Game Renpy", None));
+
+        let pattern = GamePattern::parse("Game?");
+        assert!(pattern.matches("Game2", None));
+        assert!(!pattern.matches("Game", None));
+    }
+
+    #[test]
+    fn test_glob_pattern_matches_literal_folder_name() {
+        let pattern = GamePattern::parse("Game [Deluxe]");
+        assert!(pattern.matches("Game [Deluxe]", None));
+        assert!(!pattern.matches("Game", None));
+
+        let pattern = GamePattern::parse("Who?");
+        assert!(pattern.matches("Who?", None));
+
+        let excludes = GamePatterns::parse(&["Game [Deluxe]".to_string()]);
+        assert!(excludes.matches("Game [Deluxe]", None));
+    }
+
+    #[test]
+    fn test_tag_pattern() {
+        let pattern = GamePattern::parse("tag:kids");
+        let infos = game_infos(GameEngine::Unity, &["Kids", "Puzzle"]);
+        assert!(pattern.needs_game_infos());
+        assert!(pattern.matches("Game", Some(&infos)));
+        assert!(!pattern.matches("Game", Some(&game_infos(GameEngine::Unity, &["Puzzle"]))));
+        assert!(!pattern.matches("tag:kids", None));
+    }
+
+    #[test]
+    fn test_engine_pattern() {
+        let pattern = GamePattern::parse("engine:renpy");
+        assert!(pattern.needs_game_infos());
+        assert!(pattern.matches("Game", Some(&game_infos(GameEngine::RenPy, &[]))));
+        assert!(!pattern.matches("Game", Some(&game_infos(GameEngine::Unity, &[]))));
+        assert!(!pattern.matches("Game", None));
+    }
+}
@@ -1,4 +1,7 @@
 pub mod auth_layer;
+pub mod auth_token;
+pub mod game_access;
+pub mod game_pattern;
 pub mod session_manager;
 pub mod user;
 pub mod user_handler;
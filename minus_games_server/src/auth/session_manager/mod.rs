@@ -2,18 +2,215 @@ use std::collections::HashMap;
 
 use axum::extract::Request;
 use axum_extra::extract::CookieJar;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
 
 use crate::auth::user::User;
+use crate::auth::user_handler::UserHandler;
+use crate::configuration::Configuration;
+use minus_games_models::session_info::SessionInfo;
+use tracing::{trace, warn};
 use uuid::Uuid;
 
-#[derive(Default)]
+const SESSIONS_FILE: &str = "sessions.json";
+
+pub struct Session {
+    pub user: Arc<User>,
+    pub created: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Only the hash of the id is stored, so the sessions file can not be used to take over a session.
+#[derive(Serialize, Deserialize)]
+struct StoredSession {
+    id_hash: String,
+    username: String,
+    created: DateTime<Utc>,
+    last_seen: DateTime<Utc>,
+}
+
 pub struct SessionManager {
-    pub sessions: HashMap<Uuid, Arc<User>>,
+    sessions: HashMap<String, Session>,
+    idle_timeout: TimeDelta,
+    max_age: TimeDelta,
+    sessions_file: Option<PathBuf>,
+}
+
+impl SessionManager {
+    pub fn new(config: &Configuration, user_handler: &UserHandler) -> Self {
+        let mut session_manager = Self {
+            sessions: HashMap::new(),
+            idle_timeout: TimeDelta::seconds(config.session_idle_timeout as i64),
+            max_age: TimeDelta::seconds(config.session_max_age as i64),
+            sessions_file: config
+                .persist_sessions
+                .then(|| config.data_folder.join(SESSIONS_FILE)),
+        };
+        session_manager.load(user_handler);
+        session_manager
+    }
+
+    /// Returns the user of a session that is not expired and marks the session as seen.
+    /// The user is read again, since the cli can change the user files while the server runs.
+    pub fn get_user(&mut self, id: &Uuid, user_handler: &UserHandler) -> Option<Arc<User>> {
+        let now = Utc::now();
+        let id_hash = hash_session_id(id);
+        let session = self.sessions.get_mut(&id_hash)?;
+        if session.last_seen + self.idle_timeout < now || session.created + self.max_age < now {
+            trace!("Session {id_hash} of {} expired", session.user.username);
+            self.sessions.remove(&id_hash);
+            return None;
+        }
+        let Some(user) = user_handler
+            .get_user(&session.user.username)
+            .filter(|user| user.password == session.user.password)
+        else {
+            trace!(
+                "User {} of session {id_hash} changed",
+                session.user.username
+            );
+            self.sessions.remove(&id_hash);
+            return None;
+        };
+        session.last_seen = now;
+        session.user = Arc::new(user);
+        Some(session.user.clone())
+    }
+
+    pub fn insert(&mut self, id: Uuid, user: Arc<User>) {
+        let now = Utc::now();
+        self.sessions.insert(
+            hash_session_id(&id),
+            Session {
+                user,
+                created: now,
+                last_seen: now,
+            },
+        );
+    }
+
+    /// Removes the session by the hash of its id, like it is listed in the session infos.
+    pub fn remove(&mut self, id_hash: &str) -> bool {
+        let removed = self.sessions.remove(id_hash).is_some();
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    pub fn remove_sessions_of_user(&mut self, username: &str) {
+        self.sessions
+            .retain(|_, session| session.user.username != username);
+        self.save();
+    }
+
+    pub fn remove_expired(&mut self) {
+        let now = Utc::now();
+        let (idle_timeout, max_age) = (self.idle_timeout, self.max_age);
+        self.sessions.retain(|_, session| {
+            session.last_seen + idle_timeout >= now && session.created + max_age >= now
+        });
+    }
+
+    pub fn get_session_infos(&self) -> Vec<SessionInfo> {
+        let mut session_infos: Vec<SessionInfo> = self
+            .sessions
+            .iter()
+            .map(|(id_hash, session)| SessionInfo {
+                id: id_hash.clone(),
+                username: session.user.username.clone(),
+                created: session.created,
+                last_seen: session.last_seen,
+                expires: (session.last_seen + self.idle_timeout)
+                    .min(session.created + self.max_age),
+            })
+            .collect();
+        session_infos.sort_by_key(|session_info| session_info.created);
+        session_infos
+    }
+
+    pub fn save(&self) {
+        let Some(sessions_file) = self.sessions_file.as_ref() else {
+            return;
+        };
+        let stored_sessions: Vec<StoredSession> = self
+            .sessions
+            .iter()
+            .map(|(id_hash, session)| StoredSession {
+                id_hash: id_hash.clone(),
+                username: session.user.username.clone(),
+                created: session.created,
+                last_seen: session.last_seen,
+            })
+            .collect();
+        let result = std::fs::File::create(sessions_file)
+            .map_err(|err| err.to_string())
+            .and_then(|file| {
+                serde_json::to_writer(BufWriter::new(file), &stored_sessions)
+                    .map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            warn!(
+                "Failed to save sessions at: {} With Error: {err}",
+                sessions_file.display()
+            );
+        }
+    }
+
+    fn load(&mut self, user_handler: &UserHandler) {
+        let Some(sessions_file) = self.sessions_file.as_ref() else {
+            return;
+        };
+        let Ok(file) = std::fs::File::open(sessions_file) else {
+            return;
+        };
+        let stored_sessions: Vec<StoredSession> =
+            match serde_json::from_reader(BufReader::new(file)) {
+                Ok(stored_sessions) => stored_sessions,
+                Err(err) => {
+                    warn!(
+                        "Failed to parse sessions at: {} With Error: {err}",
+                        sessions_file.display()
+                    );
+                    return;
+                }
+            };
+        for stored_session in stored_sessions {
+            // Users may have been changed or removed while the server was down.
+            if let Some(user) = user_handler.get_user(&stored_session.username) {
+                self.sessions.insert(
+                    stored_session.id_hash,
+                    Session {
+                        user: Arc::new(user),
+                        created: stored_session.created,
+                        last_seen: stored_session.last_seen,
+                    },
+                );
+            }
+        }
+        self.remove_expired();
+    }
+}
+
+pub async fn remove_expired_sessions(session_manager: Arc<RwLock<SessionManager>>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(60));
+    loop {
+        interval.tick().await;
+        let mut session_manager = session_manager.write().await;
+        session_manager.remove_expired();
+        session_manager.save();
+    }
 }
 
-impl SessionManager {}
+fn hash_session_id(id: &Uuid) -> String {
+    blake3::hash(id.as_bytes()).to_hex().to_string()
+}
 
 pub static COOKIES_SESSION_NAME: &str = "minus_games_session";
 
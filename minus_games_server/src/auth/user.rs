@@ -1,3 +1,5 @@
+use crate::auth::game_pattern::GamePatterns;
+use crate::configuration::Configuration;
 use axum::extract::FromRequestParts;
 use axum::http::StatusCode;
 use axum::http::request::Parts;
@@ -7,20 +9,182 @@ use std::ops::Deref;
 use std::sync::Arc;
 use utoipa::ToSchema;
 
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    clap::ValueEnum,
+    ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Guest,
+    Player,
+    Uploader,
+    Admin,
+}
+
+impl Role {
+    /// Every role includes the permissions of the roles below it.
+    pub fn get_included_roles(&self) -> Vec<Role> {
+        [Role::Guest, Role::Player, Role::Uploader, Role::Admin]
+            .into_iter()
+            .filter(|role| role <= self)
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct Group {
+    pub name: String,
+    #[serde(default)]
+    pub include_list: Vec<String>,
+    #[serde(default)]
+    pub exclude_list: Vec<String>,
+    #[serde(default)]
+    pub roles: Vec<Role>,
+}
+
 #[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct User {
     pub username: String,
     pub password: String,
     pub include_list: Vec<String>,
     pub exclude_list: Vec<String>,
+    #[serde(default)]
+    pub groups: Vec<String>,
+    #[serde(default)]
+    pub roles: Vec<Role>,
+    pub sync: bool,
+    /// Only read from older user files, it is converted into [`Role::Admin`].
+    #[serde(default, rename = "is_superuser", skip_serializing)]
+    #[schema(ignore)]
+    pub legacy_is_superuser: bool,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(skip)]
+    #[schema(ignore)]
+    pub resolved_groups: Vec<Group>,
+}
+
+impl User {
+    /// The highest role of the user and its groups. Users without any role are players.
+    pub fn get_role(&self) -> Role {
+        self.roles
+            .iter()
+            .chain(self.resolved_groups.iter().flat_map(|group| &group.roles))
+            .max()
+            .copied()
+            .unwrap_or(Role::Player)
+    }
+
+    pub fn has_role(&self, role: Role) -> bool {
+        self.get_role() >= role
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.has_role(Role::Admin)
+    }
+
+    pub fn get_include_list(&self) -> Vec<&String> {
+        self.include_list
+            .iter()
+            .chain(
+                self.resolved_groups
+                    .iter()
+                    .flat_map(|group| &group.include_list),
+            )
+            .collect()
+    }
+
+    pub fn get_exclude_list(&self) -> Vec<&String> {
+        self.exclude_list
+            .iter()
+            .chain(
+                self.resolved_groups
+                    .iter()
+                    .flat_map(|group| &group.exclude_list),
+            )
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct UserInfo {
+    pub username: String,
+    pub include_list: Vec<String>,
+    pub exclude_list: Vec<String>,
+    pub groups: Vec<String>,
+    pub roles: Vec<Role>,
+    pub sync: bool,
+    pub disabled: bool,
+}
+
+impl From<&User> for UserInfo {
+    fn from(user: &User) -> Self {
+        Self {
+            username: user.username.clone(),
+            include_list: user.include_list.clone(),
+            exclude_list: user.exclude_list.clone(),
+            groups: user.groups.clone(),
+            roles: user.roles.clone(),
+            sync: user.sync,
+            disabled: user.disabled,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct NewUser {
+    pub username: String,
+    pub password: String,
+    #[serde(default)]
+    pub include_list: Vec<String>,
+    #[serde(default)]
+    pub exclude_list: Vec<String>,
+    #[serde(default)]
+    pub groups: Vec<String>,
+    #[serde(default)]
+    pub roles: Vec<Role>,
+    #[serde(default)]
     pub sync: bool,
-    pub is_superuser: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct UpdateUser {
+    pub password: Option<String>,
+    pub include_list: Option<Vec<String>>,
+    pub exclude_list: Option<Vec<String>>,
+    pub groups: Option<Vec<String>>,
+    pub roles: Option<Vec<Role>>,
+    pub sync: Option<bool>,
+    pub disabled: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct ChangePassword {
+    pub old_password: String,
+    pub new_password: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, ToSchema)]
 struct UserShort<'a> {
     username: &'a str,
     sync: bool,
+    effective_roles: Vec<Role>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+struct UserMe {
+    #[serde(flatten)]
+    user_info: UserInfo,
+    effective_roles: Vec<Role>,
 }
 
 impl Default for User {
@@ -30,8 +194,12 @@ impl Default for User {
             password: "default".to_string(),
             include_list: vec![],
             exclude_list: vec![],
-            is_superuser: true,
+            groups: vec![],
+            roles: vec![Role::Admin],
             sync: true,
+            legacy_is_superuser: false,
+            disabled: false,
+            resolved_groups: vec![],
         }
     }
 }
@@ -48,51 +216,61 @@ impl Display for User {
 pub struct ArcUser(Arc<User>);
 
 impl ArcUser {
-    pub fn filter_games_list(&self, mut games: Vec<String>) -> Vec<String> {
-        if self.is_superuser {
+    pub fn filter_games_list(&self, mut games: Vec<String>, config: &Configuration) -> Vec<String> {
+        if self.is_admin() {
             return games;
         }
 
-        if !self.include_list.is_empty() {
-            games.retain_mut(|i| self.include_list.contains(i));
-        }
-
-        if !self.exclude_list.is_empty() {
-            games.retain_mut(|i| !self.exclude_list.contains(i));
-        }
+        let include_list = GamePatterns::parse(self.get_include_list());
+        let exclude_list = GamePatterns::parse(self.get_exclude_list());
+        games.retain(|game| is_game_allowed_by(&include_list, &exclude_list, game, config));
 
         games
     }
 
-    pub fn is_game_allowed(&self, game: &String) -> bool {
-        if self.is_superuser {
+    pub fn is_game_allowed(&self, game: &str, config: &Configuration) -> bool {
+        if self.is_admin() {
             return true;
         }
 
-        if !self.include_list.is_empty() && !self.include_list.contains(game) {
-            return false;
-        }
-
-        if !self.exclude_list.is_empty() && self.include_list.contains(game) {
-            return false;
-        }
-
-        true
+        let include_list = GamePatterns::parse(self.get_include_list());
+        let exclude_list = GamePatterns::parse(self.get_exclude_list());
+        is_game_allowed_by(&include_list, &exclude_list, game, config)
     }
 
     pub fn to_json_string(&self) -> String {
-        if self.is_superuser {
-            serde_json::to_string(&self.deref()).unwrap()
+        let effective_roles = self.get_role().get_included_roles();
+        if self.is_admin() {
+            let user_me = UserMe {
+                user_info: UserInfo::from(self.deref()),
+                effective_roles,
+            };
+            serde_json::to_string(&user_me).unwrap()
         } else {
             let user_short = UserShort {
                 username: &self.username,
                 sync: self.sync,
+                effective_roles,
             };
             serde_json::to_string(&user_short).unwrap()
         }
     }
 }
 
+/// A game is allowed, if it matches the include list (or the list is empty) and doesn't match the exclude list.
+fn is_game_allowed_by(
+    include_list: &GamePatterns,
+    exclude_list: &GamePatterns,
+    game: &str,
+    config: &Configuration,
+) -> bool {
+    let game_infos = (include_list.needs_game_infos() || exclude_list.needs_game_infos())
+        .then(|| config.get_game_infos(game))
+        .flatten();
+    (include_list.is_empty() || include_list.matches(game, game_infos.as_ref()))
+        && !exclude_list.matches(game, game_infos.as_ref())
+}
+
 impl Deref for ArcUser {
     type Target = User;
 
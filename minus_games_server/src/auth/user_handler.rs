@@ -1,13 +1,20 @@
-use crate::auth::user::User;
+use crate::auth::auth_token::{StoredAuthToken, split_token};
+use crate::auth::user::{Group, NewUser, Role, UpdateUser, User};
+use crate::utils::safe_join_name;
 use axum::extract::Request;
 use axum::http::header::AUTHORIZATION;
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
 use log::warn;
-use minus_games_utils::verify_argon2_hash;
-use std::io::BufReader;
+use minus_games_models::auth_token::{AuthToken, AuthTokenInfo};
+use minus_games_utils::constants::{ADDITIONS, INFOS};
+use minus_games_utils::{create_argon2_hash, is_argon2_hash, verify_argon2_hash};
+use std::fmt::{Display, Formatter};
+use std::io::{BufReader, BufWriter, ErrorKind};
 use std::path::PathBuf;
+use std::str::FromStr;
 use tracing::trace;
+use uuid::Uuid;
 
 pub struct UserHandler {
     pub user_files_path: PathBuf,
@@ -15,14 +22,25 @@ pub struct UserHandler {
 
 pub const DEFAULT_USERNAME: &str = "default";
 pub const DEFAULT_PASSWORD: &str = "default";
+const TOKENS: &str = "tokens";
+const GROUPS: &str = "groups";
+
+pub enum Authorization {
+    Basic(String, String),
+    Bearer(String),
+}
 
 impl UserHandler {
-    pub fn get_authorization_from_request(&self, request: &Request) -> Option<(String, String)> {
+    pub fn get_authorization_from_request(&self, request: &Request) -> Option<Authorization> {
         let authorization = request.headers().get(AUTHORIZATION)?;
         let auth = authorization.to_str().ok()?;
         let mut split = auth.split(' ');
         let auth_type = split.next()?;
 
+        if auth_type == "Bearer" {
+            return Some(Authorization::Bearer(split.next()?.to_string()));
+        }
+
         if auth_type != "Basic" {
             return None;
         }
@@ -34,7 +52,16 @@ impl UserHandler {
         let username = auth_value.next()?.to_string();
         let password = auth_value.next()?.to_string();
 
-        Some((username, password))
+        Some(Authorization::Basic(username, password))
+    }
+
+    pub fn authorize(&self, authorization: &Authorization) -> Option<User> {
+        match authorization {
+            Authorization::Basic(username, password) => {
+                self.authorize_user_by_username_password(username, password)
+            }
+            Authorization::Bearer(token) => self.authorize_user_by_token(token),
+        }
     }
 
     pub fn get_default_user(&self) -> User {
@@ -48,26 +75,443 @@ impl UserHandler {
         password: &str,
     ) -> Option<User> {
         trace!("Authorized: {username} - {password}");
-        let user_file_path = self.user_files_path.join(format!("{username}.json"));
-        let user_file = std::fs::File::open(user_file_path).ok()?;
-        let reader = BufReader::new(user_file);
+        let user = self.get_user(username)?;
+        if verify_argon2_hash(password, user.password.as_str()) {
+            return Some(user);
+        }
+        None
+    }
 
-        let user: User = match serde_json::from_reader(reader) {
-            Ok(user) => user,
+    pub fn authorize_user_by_token(&self, token: &str) -> Option<User> {
+        let (id, secret) = split_token(token)?;
+        let stored_token = self.get_token(&id)?;
+        if !stored_token.verify(secret) {
+            return None;
+        }
+        trace!(
+            "Authorized: {} with token {}",
+            stored_token.username, stored_token.device_name
+        );
+        self.get_user(&stored_token.username)
+    }
+
+    /// Returns the user, if it exists and is not disabled.
+    pub fn get_user(&self, username: &str) -> Option<User> {
+        match self.read_user(username) {
+            Ok(user) if user.disabled => {
+                trace!("User {username} is disabled");
+                None
+            }
+            Ok(mut user) => {
+                user.resolved_groups = user
+                    .groups
+                    .iter()
+                    .filter_map(|group| match self.read_group(group) {
+                        Ok(group) => Some(group),
+                        Err(err) => {
+                            warn!("Group {group} of {username}: {err}");
+                            None
+                        }
+                    })
+                    .collect();
+                Some(user)
+            }
+            Err(UserError::NotFound) => None,
             Err(err) => {
-                warn!(
-                    "Failed to parse user data in json file : {} With Error: {}",
-                    self.user_files_path
-                        .join(format!("{username}.json"))
-                        .display(),
-                    err
-                );
-                return None;
+                warn!("{err}");
+                None
+            }
+        }
+    }
+
+    pub fn read_user(&self, username: &str) -> Result<User, UserError> {
+        let user_file_path = self
+            .get_user_file_path(username)
+            .ok_or(UserError::NotFound)?;
+        let user_file = std::fs::File::open(user_file_path.as_path()).map_err(|err| {
+            if err.kind() == ErrorKind::NotFound {
+                UserError::NotFound
+            } else {
+                UserError::Io(format!("{}: {err}", user_file_path.display()))
             }
+        })?;
+        let mut user: User = serde_json::from_reader(BufReader::new(user_file)).map_err(|err| {
+            UserError::Invalid(format!(
+                "Failed to parse user data in json file: {} With Error: {err}",
+                user_file_path.display()
+            ))
+        })?;
+        validate_user(&user, username).map_err(|err| {
+            UserError::Invalid(format!(
+                "Invalid user data in json file: {} With Error: {err}",
+                user_file_path.display()
+            ))
+        })?;
+        if user.legacy_is_superuser && !user.roles.contains(&Role::Admin) {
+            user.roles.push(Role::Admin);
+        }
+        user.legacy_is_superuser = false;
+        Ok(user)
+    }
+
+    /// Reads every user file, including the ones which can't be parsed.
+    pub fn read_users(&self) -> Vec<(String, Result<User, UserError>)> {
+        let Ok(read_dir) = self.user_files_path.read_dir() else {
+            return Vec::new();
         };
-        if verify_argon2_hash(password, user.password.as_str()) {
-            return Some(user);
+        let mut usernames: Vec<String> = read_dir
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+            .collect();
+        usernames.sort();
+        usernames
+            .into_iter()
+            .map(|username| {
+                let user = self.read_user(&username);
+                (username, user)
+            })
+            .collect()
+    }
+
+    /// Logs every user file which would be rejected on login.
+    pub fn check_users(&self) -> bool {
+        let mut is_valid = true;
+        for (_, user) in self.read_users() {
+            if let Err(err) = user {
+                warn!("{err}");
+                is_valid = false;
+            }
         }
-        None
+        is_valid
+    }
+
+    pub fn create_user(&self, new_user: NewUser) -> Result<User, UserError> {
+        if !is_valid_username(&new_user.username) {
+            return Err(UserError::Invalid(format!(
+                "Invalid username: {}",
+                new_user.username
+            )));
+        }
+        match self.read_user(&new_user.username) {
+            Err(UserError::NotFound) => {}
+            _ => return Err(UserError::AlreadyExists),
+        }
+        self.check_groups(&new_user.groups)?;
+        let user = User {
+            password: hash_password(&new_user.password)?,
+            username: new_user.username,
+            include_list: new_user.include_list,
+            exclude_list: new_user.exclude_list,
+            groups: new_user.groups,
+            roles: new_user.roles,
+            sync: new_user.sync,
+            ..User::default()
+        };
+        self.save_user(&user)?;
+        Ok(user)
+    }
+
+    pub fn update_user(&self, username: &str, update_user: UpdateUser) -> Result<User, UserError> {
+        let mut user = self.read_user(username)?;
+        // A new password or disabling the user ends every access with the old credentials
+        let revoke_tokens =
+            update_user.password.is_some() || update_user.disabled.is_some_and(|d| d);
+        if let Some(password) = update_user.password {
+            user.password = hash_password(&password)?;
+        }
+        if let Some(include_list) = update_user.include_list {
+            user.include_list = include_list;
+        }
+        if let Some(exclude_list) = update_user.exclude_list {
+            user.exclude_list = exclude_list;
+        }
+        if let Some(sync) = update_user.sync {
+            user.sync = sync;
+        }
+        if let Some(groups) = update_user.groups {
+            self.check_groups(&groups)?;
+            user.groups = groups;
+        }
+        if let Some(roles) = update_user.roles {
+            user.roles = roles;
+        }
+        if let Some(disabled) = update_user.disabled {
+            user.disabled = disabled;
+        }
+        self.save_user(&user)?;
+        if revoke_tokens {
+            self.revoke_tokens_for_user(username);
+        }
+        Ok(user)
+    }
+
+    pub fn change_password(
+        &self,
+        username: &str,
+        old_password: &str,
+        new_password: &str,
+    ) -> Result<(), UserError> {
+        if self
+            .authorize_user_by_username_password(username, old_password)
+            .is_none()
+        {
+            return Err(UserError::WrongPassword);
+        }
+        self.update_user(
+            username,
+            UpdateUser {
+                password: Some(new_password.to_string()),
+                ..UpdateUser::default()
+            },
+        )
+        .map(|_| ())
+    }
+
+    /// Deletes the user together with all of its tokens.
+    pub fn delete_user(&self, username: &str) -> Result<(), UserError> {
+        self.read_user(username)?;
+        self.revoke_tokens_for_user(username);
+        let user_file_path = self
+            .get_user_file_path(username)
+            .ok_or(UserError::NotFound)?;
+        std::fs::remove_file(user_file_path.as_path())
+            .map_err(|err| UserError::Io(format!("{}: {err}", user_file_path.display())))
+    }
+
+    fn save_user(&self, user: &User) -> Result<(), UserError> {
+        let user_file_path = self
+            .get_user_file_path(&user.username)
+            .ok_or_else(|| UserError::Invalid(format!("Invalid username: {}", user.username)))?;
+        std::fs::create_dir_all(self.user_files_path.as_path())
+            .and_then(|_| std::fs::File::create(user_file_path.as_path()))
+            .map_err(|err| err.to_string())
+            .and_then(|file| {
+                serde_json::to_writer_pretty(BufWriter::new(file), user)
+                    .map_err(|err| err.to_string())
+            })
+            .map_err(|err| UserError::Io(format!("{}: {err}", user_file_path.display())))
+    }
+
+    pub fn read_group(&self, name: &str) -> Result<Group, UserError> {
+        let group_file_path = self.get_group_file_path(name).ok_or(UserError::NotFound)?;
+        let group_file = std::fs::File::open(group_file_path.as_path()).map_err(|err| {
+            if err.kind() == ErrorKind::NotFound {
+                UserError::NotFound
+            } else {
+                UserError::Io(format!("{}: {err}", group_file_path.display()))
+            }
+        })?;
+        serde_json::from_reader(BufReader::new(group_file)).map_err(|err| {
+            UserError::Invalid(format!(
+                "Failed to parse group data in json file: {} With Error: {err}",
+                group_file_path.display()
+            ))
+        })
+    }
+
+    pub fn read_groups(&self) -> Vec<Group> {
+        let Ok(read_dir) = self.user_files_path.join(GROUPS).read_dir() else {
+            return Vec::new();
+        };
+        let mut groups: Vec<Group> = read_dir
+            .flatten()
+            .filter_map(|entry| Some(entry.path().file_stem()?.to_str()?.to_string()))
+            .filter_map(|name| match self.read_group(&name) {
+                Ok(group) => Some(group),
+                Err(err) => {
+                    warn!("{err}");
+                    None
+                }
+            })
+            .collect();
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        groups
+    }
+
+    pub fn save_group(&self, group: &Group) -> Result<(), UserError> {
+        if !is_valid_username(&group.name) {
+            return Err(UserError::Invalid(format!(
+                "Invalid group name: {}",
+                group.name
+            )));
+        }
+        let group_file_path = self
+            .get_group_file_path(&group.name)
+            .ok_or_else(|| UserError::Invalid(format!("Invalid group name: {}", group.name)))?;
+        std::fs::create_dir_all(self.user_files_path.join(GROUPS))
+            .and_then(|_| std::fs::File::create(group_file_path.as_path()))
+            .map_err(|err| err.to_string())
+            .and_then(|file| {
+                serde_json::to_writer_pretty(BufWriter::new(file), group)
+                    .map_err(|err| err.to_string())
+            })
+            .map_err(|err| UserError::Io(format!("{}: {err}", group_file_path.display())))
+    }
+
+    pub fn delete_group(&self, name: &str) -> Result<(), UserError> {
+        self.read_group(name)?;
+        let group_file_path = self.get_group_file_path(name).ok_or(UserError::NotFound)?;
+        std::fs::remove_file(group_file_path.as_path())
+            .map_err(|err| UserError::Io(format!("{}: {err}", group_file_path.display())))
+    }
+
+    /// Usernames of all users, which are members of the group.
+    pub fn get_group_members(&self, name: &str) -> Vec<String> {
+        self.read_users()
+            .into_iter()
+            .filter_map(|(username, user)| {
+                user.ok()
+                    .filter(|user| user.groups.iter().any(|group| group == name))
+                    .map(|_| username)
+            })
+            .collect()
+    }
+
+    fn check_groups(&self, groups: &[String]) -> Result<(), UserError> {
+        for group in groups {
+            match self.read_group(group) {
+                Ok(_) => {}
+                Err(UserError::NotFound) => {
+                    return Err(UserError::Invalid(format!("Group {group} does not exist")));
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    fn get_group_file_path(&self, name: &str) -> Option<PathBuf> {
+        safe_join_name(&self.user_files_path.join(GROUPS), &format!("{name}.json"))
+    }
+
+    fn get_user_file_path(&self, username: &str) -> Option<PathBuf> {
+        safe_join_name(&self.user_files_path, &format!("{username}.json"))
+    }
+
+    fn get_tokens_path(&self) -> PathBuf {
+        self.user_files_path.join(TOKENS)
+    }
+
+    fn get_token_path(&self, id: &Uuid) -> PathBuf {
+        self.get_tokens_path().join(format!("{}.json", id.simple()))
+    }
+
+    fn get_token(&self, id: &Uuid) -> Option<StoredAuthToken> {
+        let token_file = std::fs::File::open(self.get_token_path(id)).ok()?;
+        serde_json::from_reader(BufReader::new(token_file)).ok()
+    }
+
+    pub fn create_token(&self, username: &str, device_name: &str) -> Option<AuthToken> {
+        let (stored_token, token) = StoredAuthToken::new(username, device_name);
+        let token_path = self.get_token_path(&stored_token.id);
+        let result = std::fs::create_dir_all(self.get_tokens_path())
+            .and_then(|_| std::fs::File::create(token_path.as_path()))
+            .map_err(|err| err.to_string())
+            .and_then(|file| {
+                serde_json::to_writer(BufWriter::new(file), &stored_token)
+                    .map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            warn!(
+                "Failed to save token at: {} With Error: {err}",
+                token_path.display()
+            );
+            return None;
+        }
+
+        Some(AuthToken {
+            id: stored_token.id.simple().to_string(),
+            token,
+        })
+    }
+
+    pub fn get_tokens_for_user(&self, username: &str) -> Vec<AuthTokenInfo> {
+        let Ok(read_dir) = self.get_tokens_path().read_dir() else {
+            return Vec::new();
+        };
+        let mut tokens: Vec<StoredAuthToken> = read_dir
+            .flatten()
+            .filter_map(|entry| std::fs::File::open(entry.path()).ok())
+            .filter_map(|file| {
+                serde_json::from_reader::<_, StoredAuthToken>(BufReader::new(file)).ok()
+            })
+            .filter(|token| token.username == username)
+            .collect();
+        tokens.sort_by_key(|token| token.created);
+        tokens.iter().map(AuthTokenInfo::from).collect()
+    }
+
+    pub fn revoke_tokens_for_user(&self, username: &str) {
+        for token in self.get_tokens_for_user(username) {
+            if let Ok(id) = Uuid::from_str(&token.id) {
+                self.revoke_token(username, &id);
+            }
+        }
+    }
+
+    /// Removes the token, if it belongs to `username`.
+    pub fn revoke_token(&self, username: &str, id: &Uuid) -> bool {
+        match self.get_token(id) {
+            Some(token) if token.username == username => {
+                std::fs::remove_file(self.get_token_path(id)).is_ok()
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum UserError {
+    NotFound,
+    AlreadyExists,
+    WrongPassword,
+    Invalid(String),
+    Io(String),
+}
+
+impl Display for UserError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UserError::NotFound => write!(f, "User not found"),
+            UserError::AlreadyExists => write!(f, "User already exists"),
+            UserError::WrongPassword => write!(f, "Wrong password"),
+            UserError::Invalid(err) => write!(f, "{err}"),
+            UserError::Io(err) => write!(f, "Failed to access user file: {err}"),
+        }
+    }
+}
+
+fn hash_password(password: &str) -> Result<String, UserError> {
+    if password.is_empty() {
+        return Err(UserError::Invalid("Password is empty".to_string()));
+    }
+    create_argon2_hash(password)
+        .ok_or_else(|| UserError::Invalid("Failed to hash password".to_string()))
+}
+
+/// Usernames are used as folder names in the data folder, so they must not collide with it.
+/// They must not collide with the static routes below `/users` either.
+fn is_valid_username(username: &str) -> bool {
+    const RESERVED: [&str; 6] = [INFOS, ADDITIONS, "saves", "users", "groups", "me"];
+    !username.is_empty()
+        && !username.starts_with('.')
+        && !RESERVED.contains(&username)
+        && username
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn validate_user(user: &User, username: &str) -> Result<(), String> {
+    if user.username != username {
+        return Err(format!(
+            "Username {} does not match the file name",
+            user.username
+        ));
+    }
+    if !is_argon2_hash(&user.password) {
+        return Err("Password is not an argon2 hash".to_string());
     }
+    Ok(())
 }
@@ -0,0 +1,240 @@
+use crate::app_state::AppState;
+use crate::auth::session_manager::SessionManager;
+use crate::auth::user::{NewUser, Role};
+use crate::auth::user_handler::UserHandler;
+use crate::configuration::Configuration;
+use crate::create_router;
+use axum::Router;
+use axum::body::Body;
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use axum::http::{Method, Request, StatusCode};
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use clap::Parser;
+use minus_games_models::game_infos::GameInfos;
+use minus_games_utils::constants::{ADDITIONS, INFOS};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::sync::RwLock;
+use tower::ServiceExt;
+
+const PASSWORD: &str = "password";
+const ALLOWED: &str = "Allowed";
+const SECRET: &str = "Secret";
+const FOLDER_HASH: &str = "v1-hash";
+
+/// Users which may access `Allowed` but not `Secret`, each restricted in another way.
+const RESTRICTED_USERS: [&str; 3] = ["included", "excluded", "tagged"];
+
+async fn create_test_app(temp_dir: &TempDir) -> Router {
+    let games_folder = temp_dir.path().join("games");
+    let data_folder = temp_dir.path().join("data");
+    let config = Configuration::parse_from([
+        "minus_games_server",
+        "--games-folder",
+        games_folder.to_str().unwrap(),
+        "--data-folder",
+        data_folder.to_str().unwrap(),
+    ]);
+
+    for game in [ALLOWED, SECRET] {
+        write_file(&games_folder.join(game).join("game.exe"));
+        write_file(&data_folder.join(ADDITIONS).join(game).join("header.jpg"));
+        write_file(&data_folder.join(INFOS).join(format!("{game}.csv")));
+        let game_infos = GameInfos {
+            name: game.to_string(),
+            folder_name: game.to_string(),
+            tags: (game == SECRET).then(|| vec!["private".to_string()]),
+            ..GameInfos::default()
+        };
+        std::fs::write(
+            data_folder.join(INFOS).join(format!("{game}.json")),
+            serde_json::to_string(&game_infos).unwrap(),
+        )
+        .unwrap();
+    }
+
+    let user_handler = UserHandler {
+        user_files_path: data_folder.join("users"),
+    };
+    for (username, include_list, exclude_list, roles) in [
+        ("admin", vec![], vec![], vec![Role::Admin]),
+        ("included", vec![ALLOWED], vec![], vec![]),
+        ("excluded", vec![], vec![SECRET], vec![]),
+        ("tagged", vec![], vec!["tag:private"], vec![]),
+    ] {
+        user_handler
+            .create_user(NewUser {
+                username: username.to_string(),
+                password: PASSWORD.to_string(),
+                include_list: include_list.into_iter().map(String::from).collect(),
+                exclude_list: exclude_list.into_iter().map(String::from).collect(),
+                groups: vec![],
+                roles,
+                sync: true,
+            })
+            .unwrap();
+        for game in [ALLOWED, SECRET] {
+            write_file(
+                &data_folder
+                    .join(username)
+                    .join(game)
+                    .join(FOLDER_HASH)
+                    .join("save.dat"),
+            );
+        }
+    }
+
+    let session_manager = Arc::new(RwLock::new(SessionManager::new(&config, &user_handler)));
+    create_router(Arc::new(AppState {
+        config,
+        user_handler: Arc::new(user_handler),
+        session_manager,
+    }))
+    .await
+}
+
+fn write_file(path: &Path) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, b"data").unwrap();
+}
+
+fn game_scoped_requests(game: &str) -> Vec<(Method, String)> {
+    vec![
+        (Method::GET, format!("/games/data/{INFOS}/{game}.json")),
+        (Method::GET, format!("/games/data/{INFOS}/{game}.csv")),
+        (
+            Method::GET,
+            format!("/games/data/{ADDITIONS}/{game}/header.jpg"),
+        ),
+        (Method::GET, format!("/download/{game}/game.exe")),
+        (
+            Method::GET,
+            format!("/download/{ADDITIONS}/{game}/header.jpg"),
+        ),
+        (Method::GET, format!("/sync/{game}/{FOLDER_HASH}")),
+        (Method::GET, format!("/sync/{game}/{FOLDER_HASH}/save.dat")),
+        (Method::GET, format!("/sync-history/{game}/{FOLDER_HASH}")),
+    ]
+}
+
+fn game_scoped_uploads(game: &str) -> Vec<(Method, String)> {
+    vec![
+        (Method::POST, format!("/sync/{game}/{FOLDER_HASH}")),
+        (
+            Method::POST,
+            format!("/sync-history/{game}/{FOLDER_HASH}/restore"),
+        ),
+        (Method::POST, format!("/games/upload-saves/{game}")),
+        (Method::POST, format!("/games/upload-save/{game}")),
+    ]
+}
+
+async fn send(app: &Router, username: &str, method: Method, uri: &str) -> (StatusCode, String) {
+    let credentials = BASE64_STANDARD.encode(format!("{username}:{PASSWORD}"));
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Basic {credentials}"))
+        .header(CONTENT_TYPE, "multipart/form-data; boundary=boundary")
+        .body(Body::from("--boundary--\r\n"))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_restricted_users_cannot_access_disallowed_games() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let app = create_test_app(&temp_dir).await;
+
+    for username in RESTRICTED_USERS {
+        let requests = game_scoped_requests(SECRET)
+            .into_iter()
+            .chain(game_scoped_uploads(SECRET));
+        for (method, uri) in requests {
+            let (status, _) = send(&app, username, method.clone(), &uri).await;
+            assert_eq!(
+                status,
+                StatusCode::FORBIDDEN,
+                "{username} {method} {uri} should be forbidden"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_restricted_users_can_access_allowed_games() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let app = create_test_app(&temp_dir).await;
+
+    for username in RESTRICTED_USERS {
+        for (method, uri) in game_scoped_requests(ALLOWED) {
+            let (status, _) = send(&app, username, method.clone(), &uri).await;
+            assert_eq!(
+                status,
+                StatusCode::OK,
+                "{username} {method} {uri} should be allowed"
+            );
+        }
+        for (method, uri) in game_scoped_uploads(ALLOWED) {
+            let (status, _) = send(&app, username, method.clone(), &uri).await;
+            assert_ne!(
+                status,
+                StatusCode::FORBIDDEN,
+                "{username} {method} {uri} should be allowed"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_game_lists_only_contain_allowed_games() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let app = create_test_app(&temp_dir).await;
+
+    for username in RESTRICTED_USERS {
+        let (status, body) = send(&app, username, Method::GET, "/games/list").await;
+        assert_eq!(status, StatusCode::OK);
+        let games: Vec<String> = serde_json::from_str(&body).unwrap();
+        assert_eq!(games, vec![ALLOWED.to_string()], "{username}");
+    }
+
+    let (_, body) = send(&app, "admin", Method::GET, "/games/list").await;
+    let mut games: Vec<String> = serde_json::from_str(&body).unwrap();
+    games.sort();
+    assert_eq!(games, vec![ALLOWED.to_string(), SECRET.to_string()]);
+}
+
+#[tokio::test]
+async fn test_admin_can_access_every_game() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let app = create_test_app(&temp_dir).await;
+
+    for (method, uri) in game_scoped_requests(SECRET) {
+        let (status, _) = send(&app, "admin", method.clone(), &uri).await;
+        assert_eq!(status, StatusCode::OK, "admin {method} {uri}");
+    }
+}
+
+#[tokio::test]
+async fn test_data_folder_outside_of_game_data_is_not_served() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let app = create_test_app(&temp_dir).await;
+
+    for username in ["admin", "included"] {
+        for uri in [
+            "/games/data/users/admin.json",
+            "/games/data/included/Allowed/v1-hash/save.dat",
+            "/games/data/infos/../users/admin.json",
+        ] {
+            let (status, _) = send(&app, username, Method::GET, uri).await;
+            assert_ne!(status, StatusCode::OK, "{username} {uri}");
+        }
+    }
+}
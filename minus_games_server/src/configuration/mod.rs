@@ -1,8 +1,12 @@
+use crate::auth::user::Role;
+use crate::utils::safe_join_name;
 use chrono::{DateTime, Utc};
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use minus_games_finder::configuration::Library;
 use minus_games_models::game_infos::GameInfos;
 use minus_games_utils::DataFolder;
-use minus_games_utils::constants::{ADDITIONS, INFOS};
+use minus_games_utils::constants::{ADDITIONS, INFOS, SYNC_HISTORY};
 use minus_games_utils::{GamesFolder, get_game_infos_path};
 use serde::{Deserialize, Serialize};
 use std::io::BufReader;
@@ -17,15 +21,149 @@ pub struct Configuration {
     pub port: u16,
     #[arg(long, default_value = GamesFolder {}, env = "MINUS_GAMES_GAMES_FOLDER")]
     pub games_folder: PathBuf,
+    /// Additional game libraries as name=path
+    #[arg(long = "library", env = "MINUS_GAMES_LIBRARIES", value_delimiter = ',')]
+    pub libraries: Vec<Library>,
     #[arg(long, default_value = DataFolder {}, env = "MINUS_GAMES_DATA_FOLDER")]
     pub data_folder: PathBuf,
     #[arg(long, env = "MINUS_GAMES_CACHE_FOLDER")]
     pub cache_folder: Option<PathBuf>,
     #[arg(long, env = "MINUS_GAMES_CONFIG_FILE")]
     pub config_file: Option<String>,
+    #[arg(long, default_value = "5", env = "MINUS_GAMES_SYNC_HISTORY_SIZE")]
+    pub sync_history_size: usize,
+    #[arg(long, default_value = "600", env = "MINUS_GAMES_SESSION_IDLE_TIMEOUT")]
+    pub session_idle_timeout: u64,
+    #[arg(long, default_value = "86400", env = "MINUS_GAMES_SESSION_MAX_AGE")]
+    pub session_max_age: u64,
+    #[arg(long, default_value = "false", env = "MINUS_GAMES_PERSIST_SESSIONS")]
+    pub persist_sessions: bool,
+    #[arg(long, default_value = "536870912", env = "MINUS_GAMES_MAX_UPLOAD_SIZE")]
+    pub max_upload_size: usize,
+    /// Reruns the finder every x minutes
+    #[arg(long, env = "MINUS_GAMES_FINDER_INTERVAL")]
+    pub finder_interval: Option<u64>,
+    /// Reruns the finder for games, which are added, changed or removed
+    #[arg(long, default_value = "false", env = "MINUS_GAMES_WATCH")]
+    pub watch: bool,
+    /// PEM certificate chain, enables https together with the tls key
+    #[arg(long, env = "MINUS_GAMES_TLS_CERT")]
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key
+    #[arg(long, env = "MINUS_GAMES_TLS_KEY")]
+    pub tls_key: Option<PathBuf>,
+    #[command(subcommand)]
+    pub action: Option<ServerActions>,
+}
+
+#[derive(Debug, Subcommand, Serialize, Deserialize, Clone)]
+pub enum ServerActions {
+    #[command(subcommand)]
+    User(UserActions),
+    #[command(subcommand)]
+    Group(GroupActions),
+}
+
+#[derive(Debug, Subcommand, Serialize, Deserialize, Clone)]
+pub enum GroupActions {
+    List,
+    Set {
+        name: String,
+        #[arg(long, value_enum)]
+        role: Vec<Role>,
+        #[arg(long)]
+        include: Vec<String>,
+        #[arg(long)]
+        exclude: Vec<String>,
+    },
+    Delete {
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand, Serialize, Deserialize, Clone)]
+pub enum UserActions {
+    List,
+    Check,
+    Add {
+        username: String,
+        /// Read from stdin, if not set
+        #[arg(long)]
+        password: Option<String>,
+        #[arg(long, value_enum)]
+        role: Vec<Role>,
+        #[arg(long)]
+        group: Vec<String>,
+        #[arg(long)]
+        sync: bool,
+        #[arg(long)]
+        include: Vec<String>,
+        #[arg(long)]
+        exclude: Vec<String>,
+    },
+    SetPassword {
+        username: String,
+        /// Read from stdin, if not set
+        #[arg(long)]
+        password: Option<String>,
+    },
+    Disable {
+        username: String,
+    },
+    Enable {
+        username: String,
+    },
+    Delete {
+        username: String,
+    },
 }
 
 impl Configuration {
+    /// Precedence: cli > env > config file > defaults
+    pub fn load() -> Result<Self, String> {
+        let matches = Self::command().get_matches();
+        let mut config = Self::from_arg_matches(&matches).map_err(|err| err.to_string())?;
+        if let Some(config_file) = config.config_file.as_ref() {
+            let file_configuration = FileConfiguration::read(config_file)?;
+            config.merge(file_configuration, &matches);
+        }
+        Library::validate(&config.libraries)?;
+        Ok(config)
+    }
+
+    fn merge(&mut self, file_configuration: FileConfiguration, matches: &ArgMatches) {
+        let is_default = |id: &str| {
+            !matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        };
+        macro_rules! merge {
+            ($field:ident) => {
+                if let Some(value) = file_configuration.$field
+                    && is_default(stringify!($field))
+                {
+                    self.$field = value.into();
+                }
+            };
+        }
+        merge!(ip);
+        merge!(port);
+        merge!(games_folder);
+        merge!(libraries);
+        merge!(data_folder);
+        merge!(cache_folder);
+        merge!(sync_history_size);
+        merge!(session_idle_timeout);
+        merge!(session_max_age);
+        merge!(persist_sessions);
+        merge!(max_upload_size);
+        merge!(finder_interval);
+        merge!(watch);
+        merge!(tls_cert);
+        merge!(tls_key);
+    }
+
     pub fn get_game_list(&self) -> Vec<String> {
         let path = self
             .data_folder
@@ -34,17 +172,42 @@ impl Configuration {
             .to_str()
             .unwrap()
             .to_string();
+        let libraries = self.get_libraries();
         let mut rtn: Vec<String> = Vec::new();
         for entry in glob::glob(&path).unwrap() {
             let entry = entry.unwrap();
             let file_stem = entry.file_stem().unwrap().to_str().unwrap();
 
-            if self.games_folder.join(file_stem).exists() {
+            if libraries
+                .iter()
+                .any(|library| library.path.join(file_stem).exists())
+            {
                 rtn.push(entry.file_stem().unwrap().to_str().unwrap().to_string());
             }
         }
         rtn
     }
+
+    pub fn get_libraries(&self) -> Vec<Library> {
+        Library::with_main_library(&self.games_folder, &self.libraries)
+    }
+
+    /// The root of the library the game belongs to.
+    /// Falls back to the first library containing the game for infos without a library.
+    pub fn get_library_path(&self, game: &str) -> Option<PathBuf> {
+        let libraries = self.get_libraries();
+        if let Some(library_name) = self.get_game_infos(game).and_then(|i| i.library)
+            && let Some(library) = libraries.iter().find(|i| i.name == library_name)
+            && safe_join_name(&library.path, game).is_some_and(|path| path.exists())
+        {
+            return Some(library.path.clone());
+        }
+        libraries
+            .into_iter()
+            .find(|library| safe_join_name(&library.path, game).is_some_and(|path| path.exists()))
+            .map(|library| library.path)
+    }
+
     pub fn get_modification_date_for_game(&self, name: &str) -> DateTime<Utc> {
         let path = self.get_game_infos_path_from_game(name);
         let system_time = path.metadata().unwrap().modified().unwrap();
@@ -62,6 +225,30 @@ impl Configuration {
         serde_json::from_reader(buf).ok()
     }
 
+    pub fn get_sync_folder_path(
+        &self,
+        username: &str,
+        game: &str,
+        folder_hash: &str,
+    ) -> Option<PathBuf> {
+        let user_folder = safe_join_name(&self.data_folder, username)?;
+        safe_join_name(&safe_join_name(&user_folder, game)?, folder_hash)
+    }
+
+    pub fn get_sync_history_folder_path(
+        &self,
+        username: &str,
+        game: &str,
+        folder_hash: &str,
+    ) -> Option<PathBuf> {
+        let history_folder = safe_join_name(&self.data_folder, username)?.join(SYNC_HISTORY);
+        safe_join_name(&safe_join_name(&history_folder, game)?, folder_hash)
+    }
+
+    pub fn is_tls_enabled(&self) -> bool {
+        self.tls_cert.is_some() || self.tls_key.is_some()
+    }
+
     pub fn does_game_has_header_image(&self, name: &str) -> bool {
         self.data_folder
             .join(ADDITIONS)
@@ -73,7 +260,12 @@ impl Configuration {
 
 impl std::fmt::Display for Configuration {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "Listening on: http://{}:{}", self.ip, self.port)?;
+        let scheme = if self.is_tls_enabled() {
+            "https"
+        } else {
+            "http"
+        };
+        writeln!(f, "Listening on: {scheme}://{}:{}", self.ip, self.port)?;
         writeln!(
             f,
             "Game Folder: {}",
@@ -82,6 +274,9 @@ impl std::fmt::Display for Configuration {
                 .to_str()
                 .unwrap()
         )?;
+        for library in self.libraries.iter() {
+            writeln!(f, "Library {}: {}", library.name, library.path.display())?;
+        }
         writeln!(
             f,
             "Data Folder: {}",
@@ -92,6 +287,20 @@ impl std::fmt::Display for Configuration {
         )?;
 
         writeln!(f, "Cache Folder: {:?}", self.cache_folder)?;
+        writeln!(f, "Sync History Size: {}", self.sync_history_size)?;
+        writeln!(
+            f,
+            "Session Idle Timeout: {}s, Max Age: {}s, Persist: {}",
+            self.session_idle_timeout, self.session_max_age, self.persist_sessions
+        )?;
+        writeln!(f, "Max Upload Size: {} bytes", self.max_upload_size)?;
+        match self.finder_interval {
+            None => writeln!(f, "Finder Interval: None")?,
+            Some(finder_interval) => writeln!(f, "Finder Interval: {finder_interval} min")?,
+        }
+        writeln!(f, "Watch Libraries: {}", self.watch)?;
+        writeln!(f, "TLS Cert: {:?}", self.tls_cert)?;
+        writeln!(f, "TLS Key: {:?}", self.tls_key)?;
         write!(
             f,
             "Config File: {}",
@@ -100,15 +309,33 @@ impl std::fmt::Display for Configuration {
     }
 }
 
-#[derive(Parser, Debug, Serialize, Deserialize)]
-#[command(author, version, about, long_about = None)]
+/// Toml config file, every value is optional.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct FileConfiguration {
-    #[arg(long)]
     pub ip: Option<String>,
-    #[arg(long)]
     pub port: Option<u16>,
-    #[arg(long)]
-    pub game_folder: Option<String>,
-    #[arg(long)]
-    pub data_folder: Option<String>,
+    #[serde(alias = "game_folder")]
+    pub games_folder: Option<PathBuf>,
+    pub libraries: Option<Vec<Library>>,
+    pub data_folder: Option<PathBuf>,
+    pub cache_folder: Option<PathBuf>,
+    pub sync_history_size: Option<usize>,
+    pub session_idle_timeout: Option<u64>,
+    pub session_max_age: Option<u64>,
+    pub persist_sessions: Option<bool>,
+    pub max_upload_size: Option<usize>,
+    pub finder_interval: Option<u64>,
+    pub watch: Option<bool>,
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+}
+
+impl FileConfiguration {
+    pub fn read(path: &str) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read config file: {path} With Error: {err}"))?;
+        toml::from_str(&content)
+            .map_err(|err| format!("Failed to parse config file: {path} With Error: {err}"))
+    }
 }
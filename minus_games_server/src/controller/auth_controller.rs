@@ -0,0 +1,114 @@
+use crate::app_state::AppState;
+use crate::auth::auth_layer::AuthLayer;
+use crate::auth::user::ArcUser;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use minus_games_models::auth_token::{AuthToken, AuthTokenInfo, CreateAuthToken};
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+pub(crate) const TAG: &str = "Auth Controller";
+
+pub fn new_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/tokens", get(get_tokens))
+        .route("/tokens/{id}", delete(delete_token))
+        .layer(AuthLayer::new(
+            app_state.user_handler.clone(),
+            app_state.session_manager.clone(),
+        ))
+        .route("/token", post(post_token))
+        .with_state(app_state)
+}
+
+#[utoipa::path(
+    post,
+    path = "/token",
+    request_body = CreateAuthToken,
+    responses((status = 200, description = "New API token", body = AuthToken), (status = 401, description = "Wrong username or password")),
+    context_path = "/auth",
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn post_token(
+    State(app_state): State<Arc<AppState>>,
+    Json(create_auth_token): Json<CreateAuthToken>,
+) -> Response {
+    if app_state
+        .user_handler
+        .authorize_user_by_username_password(
+            &create_auth_token.username,
+            &create_auth_token.password,
+        )
+        .is_none()
+    {
+        return (StatusCode::UNAUTHORIZED, "User is unauthorized.").into_response();
+    }
+
+    match app_state
+        .user_handler
+        .create_token(&create_auth_token.username, &create_auth_token.device_name)
+    {
+        Some(auth_token) => {
+            info!(
+                "Created token for {} on {}",
+                create_auth_token.username, create_auth_token.device_name
+            );
+            Json(auth_token).into_response()
+        }
+        None => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/tokens",
+    responses((status = 200, description = "Tokens of the current user", body = Vec < AuthTokenInfo >)),
+    context_path = "/auth",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn get_tokens(
+    State(app_state): State<Arc<AppState>>,
+    user: ArcUser,
+) -> Json<Vec<AuthTokenInfo>> {
+    Json(app_state.user_handler.get_tokens_for_user(&user.username))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/tokens/{id}",
+    params(("id", description = "Token id")),
+    responses((status = 200, description = "Token revoked"), (status = 404, description = "Token not Found")),
+    context_path = "/auth",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn delete_token(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    user: ArcUser,
+) -> StatusCode {
+    let Ok(id) = Uuid::from_str(&id) else {
+        return StatusCode::NOT_FOUND;
+    };
+    if !app_state.user_handler.revoke_token(&user.username, &id) {
+        return StatusCode::NOT_FOUND;
+    }
+
+    // Sessions don't know which token created them, so every session of the user has to log in again.
+    app_state
+        .session_manager
+        .write()
+        .await
+        .remove_sessions_of_user(&user.username);
+    info!("Revoked token {id} of {}", user.username);
+    StatusCode::OK
+}
@@ -1,13 +1,17 @@
 use crate::app_state::AppState;
 use crate::auth::auth_layer::AuthLayer;
-use crate::auth::user::ArcUser;
+use crate::auth::game_access::check_game_access;
+use crate::auth::user::{ArcUser, Role};
+use crate::utils::safe_join;
 use axum::Router;
-use axum::extract::Request;
+use axum::extract::{Request, State};
 use axum::http::StatusCode;
 use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
 use minus_games_utils::constants::ADDITIONS;
+use percent_encoding::percent_decode_str;
 use std::sync::Arc;
+use tower::ServiceExt;
 use tower_http::services::ServeDir;
 
 pub(crate) const TAG: &str = "Download Controller";
@@ -15,13 +19,16 @@ pub(crate) const TAG: &str = "Download Controller";
 pub async fn new_router(app_state: Arc<AppState>) -> Router {
     Router::new()
         .nest_service("/additions", additions_service(app_state.clone()).await)
-        .fallback_service(download_service(app_state.clone()).await)
-        .layer(axum::middleware::from_fn(check_download_access))
+        .fallback(download_game_file)
+        .layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            check_download_access,
+        ))
         .layer(AuthLayer::new(
             app_state.user_handler.clone(),
             app_state.session_manager.clone(),
-            app_state.clear_sessions.clone(),
         ))
+        .with_state(app_state)
 }
 
 #[utoipa::path(
@@ -37,29 +44,40 @@ async fn additions_service(app_state: Arc<AppState>) -> ServeDir {
         .append_index_html_on_directories(false)
 }
 
-async fn check_download_access(user: ArcUser, request: Request, next: Next) -> Response {
-    if !user.is_superuser {
-        let game_name_encoded = request
-            .uri()
-            .path()
-            .split("/")
-            .skip(1)
-            .find(|&i| i != ADDITIONS)
-            .unwrap_or_default();
+async fn check_download_access(
+    State(app_state): State<Arc<AppState>>,
+    user: ArcUser,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Ok(path) = percent_decode_str(request.uri().path()).decode_utf8() else {
+        return (StatusCode::BAD_REQUEST, "Bad Path").into_response();
+    };
+    let additions_prefix = format!("/{ADDITIONS}/");
+    let (is_addition, game_path) = match path.strip_prefix(additions_prefix.as_str()) {
+        Some(additions_path) => (true, additions_path),
+        None => (false, path.trim_start_matches('/')),
+    };
+    let game = game_path.split('/').next().unwrap_or_default();
+    // Checked against the library, which the game is served from
+    let base = if is_addition {
+        app_state.config.data_folder.join(ADDITIONS)
+    } else {
+        app_state
+            .config
+            .get_library_path(game)
+            .unwrap_or_else(|| app_state.config.games_folder.clone())
+    };
+    if safe_join(&base, game_path).is_none() {
+        return (StatusCode::BAD_REQUEST, "Bad Path").into_response();
+    }
+
+    if !is_addition && !user.has_role(Role::Player) {
+        return (StatusCode::FORBIDDEN, "Downloading games is not allowed").into_response();
+    }
 
-        let game_name_decoded = url::form_urlencoded::parse(game_name_encoded.as_bytes())
-            .next()
-            .unwrap()
-            .0
-            .to_string();
-        if !user.is_game_allowed(&game_name_decoded) {
-            // info!("Game {}", game_name_decoded);
-            return (
-                StatusCode::FORBIDDEN,
-                format!("Downloading '{game_name_decoded}' is not allowed"),
-            )
-                .into_response();
-        }
+    if let Err(rejection) = check_game_access(&user, game, &app_state) {
+        return rejection.into_response();
     }
 
     next.run(request).await
@@ -73,6 +91,24 @@ async fn check_download_access(user: ArcUser, request: Request, next: Next) -> R
     context_path = "/download",
     tag = TAG
 )]
-async fn download_service(app_state: Arc<AppState>) -> ServeDir {
-    ServeDir::new(app_state.config.games_folder.as_path()).append_index_html_on_directories(false)
+async fn download_game_file(State(app_state): State<Arc<AppState>>, request: Request) -> Response {
+    let Ok(path) = percent_decode_str(request.uri().path()).decode_utf8() else {
+        return (StatusCode::BAD_REQUEST, "Bad Path").into_response();
+    };
+    let game = path
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    let Some(library_path) = app_state.config.get_library_path(game) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match ServeDir::new(library_path)
+        .append_index_html_on_directories(false)
+        .oneshot(request)
+        .await
+    {
+        Ok(response) => response.into_response(),
+        Err(err) => match err {},
+    }
 }
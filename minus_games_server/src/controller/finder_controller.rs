@@ -1,13 +1,18 @@
 use crate::app_state::AppState;
 use crate::auth::auth_layer::AuthLayer;
-use crate::utils::super_user_only;
+use crate::auth::user::Role;
+use crate::finder_jobs::{get_finder_job, get_finder_jobs, queue_finder_job};
+use crate::utils::{role_required, safe_join_name};
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
-use axum::routing::post;
+use axum::routing::{get, post};
 use axum::{Json, Router, middleware};
+use minus_games_models::finder_job::FinderJob;
+use minus_games_models::library_conflict::LibraryConflict;
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, LazyLock};
-use tracing::info;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
 use utoipa::ToSchema;
 
 pub(crate) const TAG: &str = "Finder Controller";
@@ -18,22 +23,87 @@ pub async fn new_router(app_state: Arc<AppState>) -> Router {
         .route("/rerun-finder-for-game", post(post_rerun_finder_for_game))
         .route("/rerun-finder", post(post_rerun_finder))
         .route("/rerun-finder-all", post(post_rerun_finder_all))
-        .layer(middleware::from_fn(super_user_only))
+        .route("/conflicts", get(get_library_conflicts))
+        .route("/jobs", get(get_jobs))
+        .route("/jobs/{id}", get(get_job))
+        .layer(middleware::from_fn_with_state(
+            Role::Uploader,
+            role_required,
+        ))
         .layer(AuthLayer::new(
             app_state.user_handler.clone(),
             app_state.session_manager.clone(),
-            app_state.clear_sessions.clone(),
         ))
         .with_state(app_state)
 }
 
-static FINDER_IS_RUNNING: LazyLock<tokio::sync::Mutex<()>> =
-    LazyLock::new(|| tokio::sync::Mutex::new(()));
+fn is_game_in_libraries(app_state: &AppState, game: &str) -> bool {
+    app_state
+        .config
+        .get_libraries()
+        .iter()
+        .any(|library| safe_join_name(&library.path, game).is_some_and(|path| path.is_dir()))
+}
+
+fn create_finder_config(
+    app_state: &AppState,
+    keep_existing_configs: bool,
+    filter: Option<String>,
+) -> minus_games_finder::configuration::Configuration {
+    minus_games_finder::configuration::Configuration {
+        games_folder: app_state.config.games_folder.clone(),
+        libraries: app_state.config.libraries.clone(),
+        data_folder: app_state.config.data_folder.clone(),
+        cache_folder: app_state.config.cache_folder.clone(),
+        cleanup_data_folder: false,
+        keep_existing_configs,
+        filter,
+        watch: false,
+        config_file: None,
+    }
+}
+
+/// Reruns the finder like `/rerun-finder` every `finder_interval` minutes.
+pub async fn run_finder_periodically(app_state: Arc<AppState>, finder_interval: u64) {
+    let mut interval = tokio::time::interval(Duration::from_secs(finder_interval * 60));
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        queue_finder_job(
+            "Scheduled finder run".to_string(),
+            create_finder_config(&app_state, true, None),
+        );
+    }
+}
+
+/// Updates games, whose folders change in one of the libraries.
+pub fn watch_libraries(app_state: Arc<AppState>) {
+    std::thread::spawn(move || {
+        let libraries = app_state.config.get_libraries();
+        let result = minus_games_finder::watcher::watch_libraries(&libraries, |game| {
+            let name = match game {
+                Some(game) => format!("Game {game} changed"),
+                None => "Library changes were lost".to_string(),
+            };
+            info!("{name}");
+            queue_finder_job(
+                name,
+                create_finder_config(&app_state, false, game.map(str::to_string)),
+            );
+        });
+        if let Err(err) = result {
+            warn!("Failed to watch the libraries. Error: {err}");
+        }
+    });
+}
 
 #[utoipa::path(
     post,
     path = "/rerun-finder-for/{game}",
-    responses((status = 202, description = "Update the infos for a game")),
+    responses(
+        (status = 202, description = "Queues a job, which updates the infos for a game", body = FinderJob),
+        (status = 404, description = "Game not found")
+    ),
     context_path = "/finder",
     security(("basic-auth" = [])),
     tag = TAG
@@ -42,27 +112,15 @@ static FINDER_IS_RUNNING: LazyLock<tokio::sync::Mutex<()>> =
 pub async fn post_rerun_finder_for(
     game: Path<String>,
     State(app_state): State<Arc<AppState>>,
-) -> StatusCode {
-    if let Ok(lock) = FINDER_IS_RUNNING.try_lock() {
-        let config = minus_games_finder::configuration::Configuration {
-            games_folder: app_state.config.games_folder.clone(),
-            data_folder: app_state.config.data_folder.clone(),
-            cache_folder: app_state.config.cache_folder.clone(),
-            cleanup_data_folder: false,
-            keep_existing_configs: false,
-            filter: Some(game.to_owned()),
-        };
-
-        tokio::task::spawn_blocking(move || {
-            info!("Rerun Finder");
-            minus_games_finder::run(config);
-            info!("Finder finished");
-            drop(lock)
-        });
-    } else {
-        info!("Finder was already running");
+) -> Result<(StatusCode, Json<FinderJob>), StatusCode> {
+    if !is_game_in_libraries(&app_state, &game) {
+        return Err(StatusCode::NOT_FOUND);
     }
-    StatusCode::ACCEPTED
+    let job = queue_finder_job(
+        format!("Rerun finder for {}", game.0),
+        create_finder_config(&app_state, false, Some(game.0)),
+    );
+    Ok((StatusCode::ACCEPTED, Json(job)))
 }
 
 #[derive(ToSchema, Serialize, Deserialize)]
@@ -73,7 +131,10 @@ pub(crate) struct RerunFinderForGame {
 #[utoipa::path(
     post,
     path = "/rerun-finder-for-game",
-    responses((status = 202, description = "Update the infos for a game")),
+    responses(
+        (status = 202, description = "Queues a job, which updates the infos for a game", body = FinderJob),
+        (status = 404, description = "Game not found")
+    ),
     context_path = "/finder",
     request_body(content = RerunFinderForGame, content_type = "application/json"),
     security(("basic-auth" = [])),
@@ -83,90 +144,97 @@ pub(crate) struct RerunFinderForGame {
 pub async fn post_rerun_finder_for_game(
     State(app_state): State<Arc<AppState>>,
     game: Json<RerunFinderForGame>,
-) -> StatusCode {
-    if let Ok(lock) = FINDER_IS_RUNNING.try_lock() {
-        let config = minus_games_finder::configuration::Configuration {
-            games_folder: app_state.config.games_folder.clone(),
-            data_folder: app_state.config.data_folder.clone(),
-            cache_folder: app_state.config.cache_folder.clone(),
-            cleanup_data_folder: false,
-            keep_existing_configs: false,
-            filter: Some(game.0.game),
-        };
-
-        tokio::task::spawn_blocking(move || {
-            info!("Rerun Finder");
-            minus_games_finder::run(config);
-            info!("Finder finished");
-            drop(lock)
-        });
-    } else {
-        info!("Finder was already running");
+) -> Result<(StatusCode, Json<FinderJob>), StatusCode> {
+    if !is_game_in_libraries(&app_state, &game.game) {
+        return Err(StatusCode::NOT_FOUND);
     }
-    StatusCode::ACCEPTED
+    let job = queue_finder_job(
+        format!("Rerun finder for {}", game.0.game),
+        create_finder_config(&app_state, false, Some(game.0.game)),
+    );
+    Ok((StatusCode::ACCEPTED, Json(job)))
 }
 
 #[utoipa::path(
     post,
     path = "/rerun-finder",
-    responses((status = 202, description = "Updates the list of available games")),
+    responses((status = 202, description = "Queues a job, which updates the list of available games", body = FinderJob)),
     context_path = "/finder",
     security(("basic-auth" = [])),
     tag = TAG
 )]
 #[axum::debug_handler]
-pub async fn post_rerun_finder(State(app_state): State<Arc<AppState>>) -> StatusCode {
-    if let Ok(lock) = FINDER_IS_RUNNING.try_lock() {
-        let config = minus_games_finder::configuration::Configuration {
-            games_folder: app_state.config.games_folder.clone(),
-            data_folder: app_state.config.data_folder.clone(),
-            cache_folder: app_state.config.cache_folder.clone(),
-            cleanup_data_folder: false,
-            keep_existing_configs: true,
-            filter: None,
-        };
-
-        tokio::task::spawn_blocking(move || {
-            info!("Rerun Finder");
-            minus_games_finder::run(config);
-            info!("Finder finished");
-            drop(lock)
-        });
-    } else {
-        info!("Finder was already running");
-    }
-
-    StatusCode::ACCEPTED
+pub async fn post_rerun_finder(
+    State(app_state): State<Arc<AppState>>,
+) -> (StatusCode, Json<FinderJob>) {
+    let job = queue_finder_job(
+        "Rerun finder".to_string(),
+        create_finder_config(&app_state, true, None),
+    );
+    (StatusCode::ACCEPTED, Json(job))
 }
 
 #[utoipa::path(
     post,
     path = "/rerun-finder-all",
-    responses((status = 202, description = "Updates the complete list of available games")),
+    responses((status = 202, description = "Queues a job, which updates the complete list of available games", body = FinderJob)),
     context_path = "/finder",
     security(("basic-auth" = [])),
     tag = TAG
 )]
 #[axum::debug_handler]
-pub async fn post_rerun_finder_all(State(app_state): State<Arc<AppState>>) -> StatusCode {
-    if let Ok(lock) = FINDER_IS_RUNNING.try_lock() {
-        let config = minus_games_finder::configuration::Configuration {
-            games_folder: app_state.config.games_folder.clone(),
-            data_folder: app_state.config.data_folder.clone(),
-            cache_folder: app_state.config.cache_folder.clone(),
-            cleanup_data_folder: false,
-            keep_existing_configs: false,
-            filter: None,
-        };
+pub async fn post_rerun_finder_all(
+    State(app_state): State<Arc<AppState>>,
+) -> (StatusCode, Json<FinderJob>) {
+    let job = queue_finder_job(
+        "Rerun finder for all games".to_string(),
+        create_finder_config(&app_state, false, None),
+    );
+    (StatusCode::ACCEPTED, Json(job))
+}
 
-        tokio::task::spawn_blocking(move || {
-            info!("Rerun Finder");
-            minus_games_finder::run(config);
-            info!("Finder finished updating all games");
-            drop(lock)
-        });
-    } else {
-        info!("Finder was already running");
-    }
-    StatusCode::ACCEPTED
+#[utoipa::path(
+    get,
+    path = "/jobs",
+    responses((status = 200, description = "Queued, running and finished finder jobs, the newest first", body = Vec < FinderJob >)),
+    context_path = "/finder",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+pub async fn get_jobs() -> Json<Vec<FinderJob>> {
+    Json(get_finder_jobs())
+}
+
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    responses(
+        (status = 200, description = "Progress and results of a finder job", body = FinderJob),
+        (status = 404, description = "Job not found")
+    ),
+    context_path = "/finder",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+pub async fn get_job(id: Path<String>) -> Result<Json<FinderJob>, StatusCode> {
+    get_finder_job(&id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+#[utoipa::path(
+    get,
+    path = "/conflicts",
+    responses((status = 200, description = "Games which exist in more than one library", body = Vec < LibraryConflict >)),
+    context_path = "/finder",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+pub async fn get_library_conflicts(
+    State(app_state): State<Arc<AppState>>,
+) -> Json<Vec<LibraryConflict>> {
+    Json(minus_games_finder::find_library_conflicts(
+        &app_state.config.get_libraries(),
+    ))
 }
@@ -1,14 +1,20 @@
 use crate::app_state::AppState;
 use crate::auth::auth_layer::AuthLayer;
+use crate::auth::game_access::{AllowedGame, check_game_access};
 use crate::auth::user::ArcUser;
-use axum::extract::{Multipart, Path, State};
+use crate::utils::safe_join;
+use axum::extract::{DefaultBodyLimit, Multipart, Request, State};
 use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
-use axum::{Json, Router};
+use axum::{Json, Router, middleware};
 use log::warn;
 use minus_games_models::game_list::{
     GamesWithDate, GamesWithGameInfos, GamesWithInfos, GamesWithMinimalGameInfos,
 };
+use minus_games_utils::constants::{ADDITIONS, INFOS};
+use percent_encoding::percent_decode_str;
 use std::sync::Arc;
 use tower_http::services::ServeDir;
 use utoipa::ToSchema;
@@ -19,6 +25,7 @@ pub async fn new_router(app_state: Arc<AppState>) -> Router {
     Router::new()
         .route("/upload-saves/{game}", post(post_save_files))
         .route("/upload-save/{game}", post(post_save_file))
+        .layer(DefaultBodyLimit::max(app_state.config.max_upload_size))
         .route("/list", get(get_games_list))
         .route("/list-with-date", get(get_ordered_games_list))
         .route("/list-with-infos", get(get_ordered_games_infos_list))
@@ -30,11 +37,18 @@ pub async fn new_router(app_state: Arc<AppState>) -> Router {
             "/list-with-minimal-game-infos",
             get(get_ordered_with_minimal_games_infos_list),
         )
-        .nest_service("/data", data_service(app_state.clone()).await)
+        .nest_service(
+            "/data",
+            Router::new()
+                .fallback_service(data_service(app_state.clone()).await)
+                .layer(middleware::from_fn_with_state(
+                    app_state.clone(),
+                    check_data_access,
+                )),
+        )
         .layer(AuthLayer::new(
             app_state.user_handler.clone(),
             app_state.session_manager.clone(),
-            app_state.clear_sessions.clone(),
         ))
         .with_state(app_state)
 }
@@ -52,6 +66,38 @@ async fn data_service(app_state: Arc<AppState>) -> ServeDir {
         .append_index_html_on_directories(false)
 }
 
+/// Only the infos and additions of allowed games are served, the rest of the data folder is private.
+async fn check_data_access(
+    State(app_state): State<Arc<AppState>>,
+    user: ArcUser,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Ok(path) = percent_decode_str(request.uri().path()).decode_utf8() else {
+        return (StatusCode::BAD_REQUEST, "Bad Path").into_response();
+    };
+    let path = path.trim_start_matches('/');
+    let mut parts = path.splitn(2, '/');
+    let game = match (parts.next(), parts.next()) {
+        (Some(INFOS), Some(file_name)) => file_name
+            .strip_suffix(".json")
+            .or_else(|| file_name.strip_suffix(".csv")),
+        (Some(ADDITIONS), Some(additions_path)) => additions_path.split('/').next(),
+        _ => None,
+    };
+    let Some(game) = game.filter(|game| !game.is_empty()) else {
+        return (StatusCode::NOT_FOUND, "File not found").into_response();
+    };
+    if safe_join(&app_state.config.data_folder, path).is_none() {
+        return (StatusCode::BAD_REQUEST, "Bad Path").into_response();
+    }
+    if let Err(rejection) = check_game_access(&user, game, &app_state) {
+        return rejection.into_response();
+    }
+
+    next.run(request).await
+}
+
 #[derive(ToSchema)]
 #[allow(dead_code)]
 pub struct UploadFile {
@@ -68,7 +114,7 @@ pub struct UploadFiles {
     post,
     path = "/upload-saves/{game}",
     request_body(content = UploadFiles, content_type = "multipart/form-data"),
-    responses((status = 200, description = "Upload successful")),
+    responses((status = 200, description = "Upload successful"), (status = 400, description = "Bad Path")),
     context_path = "/games",
     security(("basic-auth" = [])),
     tag = TAG
@@ -76,14 +122,18 @@ pub struct UploadFiles {
 #[axum::debug_handler]
 pub async fn post_save_files(
     State(app_state): State<Arc<AppState>>,
-    game: Path<String>,
+    AllowedGame(game): AllowedGame,
     mut multipart: Multipart,
 ) -> Result<(), StatusCode> {
-    if !app_state.does_game_exist(game.as_str()) {
+    if !app_state.does_game_exist(&game) {
         return Err(StatusCode::NOT_FOUND);
     }
-    while let Some(field) = multipart.next_field().await.unwrap() {
-        app_state.write_save_file(game.as_str(), field).await;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?
+    {
+        app_state.write_save_file(&game, field).await?;
     }
     Ok(())
 }
@@ -92,7 +142,7 @@ pub async fn post_save_files(
     post,
     path = "/upload-save/{game}",
     request_body(content = UploadFile, content_type = "multipart/form-data"),
-    responses((status = 200, description = "Upload successful")),
+    responses((status = 200, description = "Upload successful"), (status = 400, description = "Bad Path")),
     context_path = "/games",
     security(("basic-auth" = [])),
     tag = TAG
@@ -100,14 +150,18 @@ pub async fn post_save_files(
 #[axum::debug_handler]
 pub async fn post_save_file(
     State(app_state): State<Arc<AppState>>,
-    game: Path<String>,
+    AllowedGame(game): AllowedGame,
     mut multipart: Multipart,
 ) -> Result<(), StatusCode> {
-    if !app_state.does_game_exist(game.as_str()) {
+    if !app_state.does_game_exist(&game) {
         return Err(StatusCode::NOT_FOUND);
     }
-    if let Some(field) = multipart.next_field().await.unwrap() {
-        app_state.write_save_file(game.as_str(), field).await;
+    if let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?
+    {
+        app_state.write_save_file(&game, field).await?;
     }
     Ok(())
 }
@@ -126,7 +180,7 @@ pub async fn get_games_list(
     user: ArcUser,
 ) -> Json<Vec<String>> {
     let game_list = app_state.config.get_game_list();
-    Json(user.filter_games_list(game_list))
+    Json(user.filter_games_list(game_list, &app_state.config))
 }
 
 #[utoipa::path(
@@ -143,7 +197,7 @@ pub async fn get_ordered_games_list(
     user: ArcUser,
 ) -> Json<Vec<GamesWithDate>> {
     let game_list = app_state.config.get_game_list();
-    let filtered_game_list = user.filter_games_list(game_list);
+    let filtered_game_list = user.filter_games_list(game_list, &app_state.config);
     let mut rtn = Vec::with_capacity(filtered_game_list.len());
     for name in filtered_game_list {
         let modification_date = app_state.config.get_modification_date_for_game(&name);
@@ -166,7 +220,7 @@ pub async fn get_ordered_games_infos_list(
     user: ArcUser,
 ) -> Json<Vec<GamesWithInfos>> {
     let game_list = app_state.config.get_game_list();
-    let filtered_game_list = user.filter_games_list(game_list);
+    let filtered_game_list = user.filter_games_list(game_list, &app_state.config);
     let mut rtn = Vec::with_capacity(filtered_game_list.len());
     for name in filtered_game_list {
         let modification_date = app_state.config.get_modification_date_for_game(&name);
@@ -190,7 +244,7 @@ pub async fn get_ordered_with_games_infos_list(
     user: ArcUser,
 ) -> Json<Vec<GamesWithGameInfos>> {
     let game_list = app_state.config.get_game_list();
-    let filtered_game_list = user.filter_games_list(game_list);
+    let filtered_game_list = user.filter_games_list(game_list, &app_state.config);
     let mut rtn = Vec::with_capacity(filtered_game_list.len());
     for name in filtered_game_list {
         let modification_date = app_state.config.get_modification_date_for_game(&name);
@@ -223,7 +277,7 @@ pub async fn get_ordered_with_minimal_games_infos_list(
     user: ArcUser,
 ) -> Json<Vec<GamesWithMinimalGameInfos>> {
     let game_list = app_state.config.get_game_list();
-    let filtered_game_list = user.filter_games_list(game_list);
+    let filtered_game_list = user.filter_games_list(game_list, &app_state.config);
     let mut rtn = Vec::with_capacity(filtered_game_list.len());
     for name in filtered_game_list {
         let modification_date = app_state.config.get_modification_date_for_game(&name);
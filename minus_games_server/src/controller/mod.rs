@@ -1,7 +1,11 @@
+pub mod auth_controller;
 pub mod client_controller;
 pub mod download_controller;
 pub mod finder_controller;
 pub mod game_controller;
 pub mod gui_controller;
+pub mod session_controller;
 pub mod sync_controller;
+pub mod sync_history_controller;
 pub mod updater_controller;
+pub mod user_controller;
@@ -0,0 +1,59 @@
+use crate::app_state::AppState;
+use crate::auth::auth_layer::AuthLayer;
+use crate::auth::user::Role;
+use crate::utils::role_required;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get};
+use axum::{Json, Router, middleware};
+use minus_games_models::session_info::SessionInfo;
+use std::sync::Arc;
+use tracing::info;
+
+pub(crate) const TAG: &str = "Session Controller";
+
+pub fn new_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(get_sessions))
+        .route("/{id}", delete(delete_session))
+        .layer(middleware::from_fn_with_state(Role::Admin, role_required))
+        .layer(AuthLayer::new(
+            app_state.user_handler.clone(),
+            app_state.session_manager.clone(),
+        ))
+        .with_state(app_state)
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    responses((status = 200, description = "All active sessions", body = Vec < SessionInfo >)),
+    context_path = "/sessions",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn get_sessions(State(app_state): State<Arc<AppState>>) -> Json<Vec<SessionInfo>> {
+    Json(app_state.session_manager.read().await.get_session_infos())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    params(("id", description = "Session id hash")),
+    responses((status = 200, description = "Session killed"), (status = 404, description = "Session not Found")),
+    context_path = "/sessions",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn delete_session(
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> StatusCode {
+    if !app_state.session_manager.write().await.remove(&id) {
+        return StatusCode::NOT_FOUND;
+    }
+    info!("Killed session {id}");
+    StatusCode::OK
+}
@@ -1,6 +1,11 @@
 use crate::app_state::AppState;
 use crate::auth::auth_layer::AuthLayer;
+use crate::auth::game_access::AllowedGame;
 use crate::auth::user::ArcUser;
+use crate::sync_history::{
+    UPLOAD_EXTENSION, archive_sync_file, get_temp_path, lock_sync_file, sync_parent_folder,
+};
+use crate::utils::safe_join;
 use axum::body::Body;
 use axum::extract::multipart::Field;
 use axum::extract::{DefaultBodyLimit, Multipart, Path, State};
@@ -18,7 +23,7 @@ use std::time::SystemTime;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 use tokio_util::io::ReaderStream;
-use tracing::info;
+use tracing::{info, warn};
 use utoipa::ToSchema;
 
 pub(crate) const TAG: &str = "Sync Controller";
@@ -28,11 +33,10 @@ pub fn new_router(app_state: Arc<AppState>) -> Router {
         .route("/{game}/{folder_hash}", get(get_sync_files_for_folder))
         .route("/{game}/{folder_hash}", post(post_sync_file_for_folder))
         .route("/{game}/{folder_hash}/{*file_path}", get(get_sync_file))
-        .layer(DefaultBodyLimit::max(1024 * 1024 * 512))
+        .layer(DefaultBodyLimit::max(app_state.config.max_upload_size))
         .layer(AuthLayer::new(
             app_state.user_handler.clone(),
             app_state.session_manager.clone(),
-            app_state.clear_sessions.clone(),
         ))
         .with_state(app_state)
 }
@@ -41,23 +45,24 @@ pub fn new_router(app_state: Arc<AppState>) -> Router {
     get,
     path = "/{game}/{folder_hash}/{file_path}",
     params(("game", description = "Game name"), ("folder_hash", description = "Folder Hash"), ("file_path", description = "Path to save file")),
-    responses((status = 200, description = "File"), (status = 404, description = "File not Found")),
+    responses((status = 200, description = "File"), (status = 400, description = "Bad Path"), (status = 404, description = "File not Found")),
     context_path = "/sync",
     tag = TAG
 )]
 #[axum::debug_handler]
 async fn get_sync_file(
-    Path((game, folder_hash, file_path)): Path<(String, String, String)>,
+    Path((_game, folder_hash, file_path)): Path<(String, String, String)>,
+    AllowedGame(game): AllowedGame,
     State(app_state): State<Arc<AppState>>,
     user: ArcUser,
 ) -> Response {
-    let save_path = app_state
+    let Some(save_path) = app_state
         .config
-        .data_folder
-        .join(&user.username)
-        .join(game)
-        .join(folder_hash)
-        .join(file_path);
+        .get_sync_folder_path(&user.username, &game, &folder_hash)
+        .and_then(|sync_folder| safe_join(&sync_folder, &file_path))
+    else {
+        return (StatusCode::BAD_REQUEST, "Bad Path").into_response();
+    };
     info!("Download sync file {}", save_path.display());
     send_file(save_path).await
 }
@@ -98,19 +103,21 @@ async fn send_file(save_path: PathBuf) -> Response {
 )]
 #[axum::debug_handler]
 async fn get_sync_files_for_folder(
-    Path((game, folder_hash)): Path<(String, String)>,
+    Path((_game, folder_hash)): Path<(String, String)>,
+    AllowedGame(game): AllowedGame,
     State(app_state): State<Arc<AppState>>,
     user: ArcUser,
 ) -> Json<Option<Vec<SyncFileInfo>>> {
     let save_path = app_state
         .config
-        .data_folder
-        .join(&user.username)
-        .join(game)
-        .join(folder_hash);
-    let save_path_ref = save_path.as_path();
-    if save_path_ref.is_dir() {
-        let sync_files_infos = create_sync_file_infos_from_path(save_path.as_path());
+        .get_sync_folder_path(&user.username, &game, &folder_hash);
+    if let Some(save_path) = save_path
+        && save_path.is_dir()
+    {
+        let sync_files_infos = create_sync_file_infos_from_path(save_path.as_path())
+            .into_iter()
+            .filter(|sync_file_info| !sync_file_info.file_name.ends_with(UPLOAD_EXTENSION))
+            .collect();
         Json::from(Some(sync_files_infos))
     } else {
         Json::from(None)
@@ -124,6 +131,7 @@ pub struct UploadSyncFile {
     file_path: String,
     size: u64,
     last_modified: DateTime<Utc>,
+    base_last_modified: Option<DateTime<Utc>>,
     upload_data: Vec<u8>,
 }
 
@@ -132,76 +140,124 @@ pub struct UploadSyncFile {
     path = "/{game}/{folder_hash}",
     params(("game", description = "Game name"), ("folder_hash", description = "Folder Hash")),
     request_body(content = UploadSyncFile, content_type = "multipart/form-data"),
-    responses((status = 200 )),
+    responses((status = 200 ), (status = 400, description = "Bad Path or size mismatch"), (status = 409, description = "Base version is stale")),
     context_path = "/sync",
     tag = TAG
 )]
 #[axum::debug_handler]
 async fn post_sync_file_for_folder(
     State(app_state): State<Arc<AppState>>,
-    Path((game, folder_hash)): Path<(String, String)>,
+    Path((_game, folder_hash)): Path<(String, String)>,
+    AllowedGame(game): AllowedGame,
     user: ArcUser,
     mut multipart: Multipart,
 ) -> Response {
     if game.is_empty() || folder_hash.is_empty() {
-        return Response::builder()
-            .status(StatusCode::BAD_REQUEST)
-            .body(Body::from("Invalid game or folder hash"))
-            .unwrap();
+        return bad_request("Invalid game or folder hash");
     }
 
     let mut file_name: Option<String> = None;
     let mut file_path: Option<String> = None;
     let mut size: Option<u64> = None;
     let mut last_modified: Option<SystemTime> = None;
+    let mut base_last_modified: Option<DateTime<Utc>> = None;
 
-    while let Some(field) = multipart.next_field().await.unwrap() {
-        let field_name = field.name().unwrap();
-        match field_name {
-            "file_name" => {
-                file_name = Some(field.text().await.unwrap());
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(err) => return bad_request(format!("Invalid multipart data: {err}")),
+        };
+        let field_name = field.name().unwrap_or_default().to_string();
+        if field_name == "upload_data" {
+            let (Some(file_path), Some(size), Some(last_modified)) =
+                (file_path.as_ref(), size, last_modified)
+            else {
+                return bad_request("Missing field");
+            };
+            if file_name.is_none() {
+                return bad_request("Missing field");
             }
-            "file_path" => {
-                file_path = Some(field.text().await.unwrap());
+
+            let history_file_folder = app_state
+                .config
+                .get_sync_history_folder_path(&user.username, &game, &folder_hash)
+                .and_then(|history_folder| safe_join(&history_folder, file_path));
+            let sync_file_path = app_state
+                .config
+                .get_sync_folder_path(&user.username, &game, &folder_hash)
+                .and_then(|sync_folder| safe_join(&sync_folder, file_path));
+            let (Some(file_path), Some(history_file_folder)) =
+                (sync_file_path, history_file_folder)
+            else {
+                return bad_request("Bad Path");
+            };
+            if is_stale_base(file_path.as_path(), base_last_modified) {
+                return Response::builder()
+                    .status(StatusCode::CONFLICT)
+                    .body(Body::from("Sync file was changed since the base version"))
+                    .unwrap();
             }
-            "size" => {
-                size = Some(field.text().await.unwrap().parse().unwrap());
+
+            let temp_file_path = match write_sync_file(file_path.as_path(), field, Some(size)).await
+            {
+                Ok(temp_file_path) => temp_file_path,
+                Err(err) => {
+                    warn!("Rejected upload of {}: {err}", file_path.display());
+                    return bad_request(err);
+                }
+            };
+            set_file_modified_time(temp_file_path.as_path(), last_modified);
+
+            // Another upload could have replaced the file while this one was written
+            let _lock = lock_sync_file(file_path.as_path()).await;
+            if is_stale_base(file_path.as_path(), base_last_modified) {
+                tokio::fs::remove_file(temp_file_path.as_path()).await.ok();
+                return Response::builder()
+                    .status(StatusCode::CONFLICT)
+                    .body(Body::from("Sync file was changed since the base version"))
+                    .unwrap();
             }
-            "last_modified" => {
-                let last_modified_str = field.text().await.unwrap();
-                let last_modified_date_time =
-                    DateTime::parse_from_rfc3339(&last_modified_str).unwrap();
-                last_modified = Some(last_modified_date_time.into());
+            archive_sync_file(
+                file_path.as_path(),
+                history_file_folder.as_path(),
+                app_state.config.sync_history_size,
+            )
+            .await;
+            if let Err(err) = tokio::fs::rename(temp_file_path.as_path(), file_path.as_path()).await
+            {
+                warn!("Failed to store sync file {}: {err}", file_path.display());
+                tokio::fs::remove_file(temp_file_path.as_path()).await.ok();
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
             }
-            "upload_data" => {
-                if file_name.is_none()
-                    || file_path.is_none()
-                    || size.is_none()
-                    || last_modified.is_none()
-                {
-                    return Response::builder()
-                        .status(StatusCode::BAD_REQUEST)
-                        .body(Body::from("Missing field"))
-                        .unwrap();
-                }
+            sync_parent_folder(file_path.as_path()).await;
+            break;
+        }
 
-                let file_path = app_state
-                    .config
-                    .data_folder
-                    .join(&user.username)
-                    .join(&game)
-                    .join(&folder_hash)
-                    .join(file_path.unwrap());
-                write_sync_file(file_path.as_path(), field).await;
-
-                set_file_modified_time(file_path.as_path(), last_modified.unwrap());
-                break;
+        let Ok(value) = field.text().await else {
+            return bad_request(format!("Invalid value for {field_name}"));
+        };
+        match field_name.as_str() {
+            "file_name" => {
+                file_name = Some(value);
+            }
+            "file_path" => {
+                file_path = Some(value);
             }
+            "size" => match value.parse() {
+                Ok(value) => size = Some(value),
+                Err(_) => return bad_request("Invalid size"),
+            },
+            "last_modified" => match DateTime::parse_from_rfc3339(&value) {
+                Ok(value) => last_modified = Some(value.into()),
+                Err(_) => return bad_request("Invalid last_modified"),
+            },
+            "base_last_modified" => match DateTime::parse_from_rfc3339(&value) {
+                Ok(value) => base_last_modified = Some(value.into()),
+                Err(_) => return bad_request("Invalid base_last_modified"),
+            },
             _ => {
-                return Response::builder()
-                    .status(StatusCode::BAD_REQUEST)
-                    .body(Body::from("Invalid field name"))
-                    .unwrap();
+                return bad_request("Invalid field name");
             }
         }
     }
@@ -209,20 +265,82 @@ async fn post_sync_file_for_folder(
     ().into_response()
 }
 
-pub async fn write_sync_file(file_path: &std::path::Path, mut field: Field<'_>) {
-    tokio::fs::create_dir_all(file_path.parent().unwrap())
+fn bad_request(message: impl Into<Body>) -> Response {
+    Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .body(message.into())
+        .unwrap()
+}
+
+fn is_stale_base(file_path: &std::path::Path, base_last_modified: Option<DateTime<Utc>>) -> bool {
+    let Some(base_last_modified) = base_last_modified else {
+        return false;
+    };
+    match file_path
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+    {
+        Ok(modified) => DateTime::<Utc>::from(modified) != base_last_modified,
+        Err(_) => false,
+    }
+}
+
+/// Streams the upload into a temp file next to `file_path` and returns the temp file once it is complete and synced.
+/// Writes the field into a temp file next to `file_path` and checks the declared size, if there is one.
+pub async fn write_sync_file(
+    file_path: &std::path::Path,
+    field: Field<'_>,
+    size: Option<u64>,
+) -> Result<PathBuf, String> {
+    let (Some(parent), Some(temp_file_path)) = (file_path.parent(), get_temp_path(file_path))
+    else {
+        return Err("Bad Path".to_string());
+    };
+    tokio::fs::create_dir_all(parent)
         .await
-        .unwrap();
-    let mut save_file = File::create(file_path).await.unwrap();
-    loop {
-        let chunk = field.chunk().await.unwrap();
-        match chunk {
-            None => {
-                break;
-            }
-            Some(bytes) => {
-                let _ = save_file.write(&bytes).await.unwrap();
-            }
+        .map_err(|err| err.to_string())?;
+
+    let result = write_field_to(temp_file_path.as_path(), field, size).await;
+    if result.is_err() {
+        tokio::fs::remove_file(temp_file_path.as_path()).await.ok();
+    }
+
+    result.map(|_| temp_file_path)
+}
+
+async fn write_field_to(
+    file_path: &std::path::Path,
+    mut field: Field<'_>,
+    size: Option<u64>,
+) -> Result<(), String> {
+    let mut save_file = File::create(file_path)
+        .await
+        .map_err(|err| err.to_string())?;
+    let mut written: u64 = 0;
+    while let Some(bytes) = field
+        .chunk()
+        .await
+        .map_err(|err| format!("Upload aborted: {err}"))?
+    {
+        save_file
+            .write_all(&bytes)
+            .await
+            .map_err(|err| err.to_string())?;
+        written += bytes.len() as u64;
+        if let Some(size) = size
+            && written > size
+        {
+            return Err(format!("Upload is larger than the declared size {size}"));
         }
     }
+
+    if let Some(size) = size
+        && written != size
+    {
+        return Err(format!(
+            "Upload size {written} does not match the declared size {size}"
+        ));
+    }
+
+    save_file.sync_all().await.map_err(|err| err.to_string())
 }
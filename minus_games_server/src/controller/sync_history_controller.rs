@@ -0,0 +1,112 @@
+use crate::app_state::AppState;
+use crate::auth::auth_layer::AuthLayer;
+use crate::auth::game_access::AllowedGame;
+use crate::auth::user::ArcUser;
+use crate::sync_history::{is_valid_revision, list_sync_history, restore_sync_file};
+use crate::utils::safe_join;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use minus_games_models::sync_file_history::{RestoreSyncFile, SyncFileHistory};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+pub(crate) const TAG: &str = "Sync History Controller";
+
+pub fn new_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/{game}/{folder_hash}", get(get_sync_history_for_folder))
+        .route(
+            "/{game}/{folder_hash}/restore",
+            post(post_restore_sync_file),
+        )
+        .layer(AuthLayer::new(
+            app_state.user_handler.clone(),
+            app_state.session_manager.clone(),
+        ))
+        .with_state(app_state)
+}
+
+#[utoipa::path(
+    get,
+    path = "/{game}/{folder_hash}",
+    params(("game", description = "Game name"), ("folder_hash", description = "Folder Hash")),
+    responses((status = 200, description = "Revisions per file", body = Vec < SyncFileHistory >)),
+    context_path = "/sync-history",
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn get_sync_history_for_folder(
+    Path((_game, folder_hash)): Path<(String, String)>,
+    AllowedGame(game): AllowedGame,
+    State(app_state): State<Arc<AppState>>,
+    user: ArcUser,
+) -> Json<Vec<SyncFileHistory>> {
+    let history = app_state
+        .config
+        .get_sync_history_folder_path(&user.username, &game, &folder_hash)
+        .map(|history_folder| list_sync_history(history_folder.as_path()))
+        .unwrap_or_default();
+    Json::from(history)
+}
+
+#[utoipa::path(
+    post,
+    path = "/{game}/{folder_hash}/restore",
+    params(("game", description = "Game name"), ("folder_hash", description = "Folder Hash")),
+    request_body = RestoreSyncFile,
+    responses((status = 200), (status = 400, description = "Invalid revision or path"), (status = 404, description = "Revision not Found")),
+    context_path = "/sync-history",
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn post_restore_sync_file(
+    Path((_game, folder_hash)): Path<(String, String)>,
+    AllowedGame(game): AllowedGame,
+    State(app_state): State<Arc<AppState>>,
+    user: ArcUser,
+    Json(restore): Json<RestoreSyncFile>,
+) -> Response {
+    if !is_valid_revision(&restore.revision) {
+        return (StatusCode::BAD_REQUEST, "Invalid revision").into_response();
+    }
+
+    let history_file_folder = app_state
+        .config
+        .get_sync_history_folder_path(&user.username, &game, &folder_hash)
+        .and_then(|history_folder| safe_join(&history_folder, &restore.file_path));
+    let sync_file_path = app_state
+        .config
+        .get_sync_folder_path(&user.username, &game, &folder_hash)
+        .and_then(|sync_folder| safe_join(&sync_folder, &restore.file_path));
+    let (Some(sync_file_path), Some(history_file_folder)) = (sync_file_path, history_file_folder)
+    else {
+        return (StatusCode::BAD_REQUEST, "Bad Path").into_response();
+    };
+
+    if !history_file_folder.join(&restore.revision).is_file() {
+        return (StatusCode::NOT_FOUND, "Revision not found").into_response();
+    }
+
+    info!(
+        "Restore {} to revision {}",
+        sync_file_path.display(),
+        restore.revision
+    );
+    match restore_sync_file(
+        sync_file_path.as_path(),
+        history_file_folder.as_path(),
+        &restore.revision,
+        app_state.config.sync_history_size,
+    )
+    .await
+    {
+        Ok(_) => ().into_response(),
+        Err(err) => {
+            warn!("Failed to restore {}: {err}", sync_file_path.display());
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -17,7 +17,6 @@ pub fn new_router(app_state: Arc<AppState>) -> Router {
         .layer(AuthLayer::new(
             app_state.user_handler.clone(),
             app_state.session_manager.clone(),
-            app_state.clear_sessions.clone(),
         ))
         .with_state(app_state)
 }
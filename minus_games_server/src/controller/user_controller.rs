@@ -0,0 +1,226 @@
+use crate::app_state::AppState;
+use crate::auth::auth_layer::AuthLayer;
+use crate::auth::user::{ArcUser, ChangePassword, Group, NewUser, Role, UpdateUser, UserInfo};
+use crate::auth::user_handler::UserError;
+use crate::utils::role_required;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, put};
+use axum::{Json, Router, middleware};
+use std::sync::Arc;
+use tracing::info;
+
+pub(crate) const TAG: &str = "User Controller";
+
+pub fn new_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(get_users).post(post_user))
+        .route("/{username}", put(put_user).delete(delete_user))
+        .route("/groups", get(get_groups))
+        .route("/groups/{name}", put(put_group).delete(delete_group))
+        .layer(middleware::from_fn_with_state(Role::Admin, role_required))
+        .route("/me/password", put(put_password))
+        .layer(AuthLayer::new(
+            app_state.user_handler.clone(),
+            app_state.session_manager.clone(),
+        ))
+        .with_state(app_state)
+}
+
+impl IntoResponse for UserError {
+    fn into_response(self) -> Response {
+        let status_code = match self {
+            UserError::NotFound => StatusCode::NOT_FOUND,
+            UserError::AlreadyExists => StatusCode::CONFLICT,
+            UserError::WrongPassword => StatusCode::UNAUTHORIZED,
+            UserError::Invalid(_) => StatusCode::BAD_REQUEST,
+            UserError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status_code, self.to_string()).into_response()
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    responses((status = 200, description = "All valid users", body = Vec < UserInfo >)),
+    context_path = "/users",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn get_users(State(app_state): State<Arc<AppState>>) -> Json<Vec<UserInfo>> {
+    Json(
+        app_state
+            .user_handler
+            .read_users()
+            .iter()
+            .filter_map(|(_, user)| user.as_ref().ok())
+            .map(UserInfo::from)
+            .collect(),
+    )
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    request_body = NewUser,
+    responses((status = 201, description = "User created", body = UserInfo), (status = 400, description = "Invalid user"), (status = 409, description = "User already exists")),
+    context_path = "/users",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn post_user(
+    State(app_state): State<Arc<AppState>>,
+    Json(new_user): Json<NewUser>,
+) -> Result<(StatusCode, Json<UserInfo>), UserError> {
+    let user = app_state.user_handler.create_user(new_user)?;
+    info!("Created user {}", user.username);
+    Ok((StatusCode::CREATED, Json(UserInfo::from(&user))))
+}
+
+#[utoipa::path(
+    put,
+    path = "/{username}",
+    params(("username", description = "Username")),
+    request_body = UpdateUser,
+    responses((status = 200, description = "User updated", body = UserInfo), (status = 404, description = "User not Found")),
+    context_path = "/users",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn put_user(
+    State(app_state): State<Arc<AppState>>,
+    Path(username): Path<String>,
+    Json(update_user): Json<UpdateUser>,
+) -> Result<Json<UserInfo>, UserError> {
+    let user = app_state.user_handler.update_user(&username, update_user)?;
+    app_state
+        .session_manager
+        .write()
+        .await
+        .remove_sessions_of_user(&username);
+    info!("Updated user {username}");
+    Ok(Json(UserInfo::from(&user)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{username}",
+    params(("username", description = "Username")),
+    responses((status = 200, description = "User deleted"), (status = 404, description = "User not Found")),
+    context_path = "/users",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn delete_user(
+    State(app_state): State<Arc<AppState>>,
+    Path(username): Path<String>,
+) -> Result<StatusCode, UserError> {
+    app_state.user_handler.delete_user(&username)?;
+    app_state
+        .session_manager
+        .write()
+        .await
+        .remove_sessions_of_user(&username);
+    info!("Deleted user {username}");
+    Ok(StatusCode::OK)
+}
+
+#[utoipa::path(
+    put,
+    path = "/me/password",
+    request_body = ChangePassword,
+    responses((status = 200, description = "Password changed"), (status = 401, description = "Wrong password")),
+    context_path = "/users",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn put_password(
+    State(app_state): State<Arc<AppState>>,
+    user: ArcUser,
+    Json(change_password): Json<ChangePassword>,
+) -> Result<StatusCode, UserError> {
+    app_state.user_handler.change_password(
+        &user.username,
+        &change_password.old_password,
+        &change_password.new_password,
+    )?;
+    app_state
+        .session_manager
+        .write()
+        .await
+        .remove_sessions_of_user(&user.username);
+    info!("Changed password of {}", user.username);
+    Ok(StatusCode::OK)
+}
+
+#[utoipa::path(
+    get,
+    path = "/groups",
+    responses((status = 200, description = "All groups", body = Vec < Group >)),
+    context_path = "/users",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn get_groups(State(app_state): State<Arc<AppState>>) -> Json<Vec<Group>> {
+    Json(app_state.user_handler.read_groups())
+}
+
+#[utoipa::path(
+    put,
+    path = "/groups/{name}",
+    params(("name", description = "Group name")),
+    request_body = Group,
+    responses((status = 200, description = "Group created or updated", body = Group), (status = 400, description = "Invalid group")),
+    context_path = "/users",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn put_group(
+    State(app_state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(mut group): Json<Group>,
+) -> Result<Json<Group>, UserError> {
+    group.name = name;
+    app_state.user_handler.save_group(&group)?;
+    remove_sessions_of_group_members(&app_state, &group.name).await;
+    info!("Saved group {}", group.name);
+    Ok(Json(group))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/groups/{name}",
+    params(("name", description = "Group name")),
+    responses((status = 200, description = "Group deleted"), (status = 404, description = "Group not Found")),
+    context_path = "/users",
+    security(("basic-auth" = [])),
+    tag = TAG
+)]
+#[axum::debug_handler]
+async fn delete_group(
+    State(app_state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, UserError> {
+    app_state.user_handler.delete_group(&name)?;
+    remove_sessions_of_group_members(&app_state, &name).await;
+    info!("Deleted group {name}");
+    Ok(StatusCode::OK)
+}
+
+/// Sessions keep the resolved groups of their user, so members have to log in again.
+async fn remove_sessions_of_group_members(app_state: &AppState, name: &str) {
+    let members = app_state.user_handler.get_group_members(name);
+    let mut session_manager = app_state.session_manager.write().await;
+    for member in members {
+        session_manager.remove_sessions_of_user(&member);
+    }
+}
@@ -0,0 +1,158 @@
+use chrono::Utc;
+use minus_games_finder::configuration::Configuration;
+use minus_games_finder::progress::FinderProgress;
+use minus_games_models::finder_job::{FinderGameResult, FinderJob, FinderJobState};
+use std::collections::VecDeque;
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+use tracing::{info, warn};
+
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// The newest job is the first one.
+static FINDER_JOBS: LazyLock<Mutex<VecDeque<FinderJob>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+/// The finder runs the queued jobs one after another on its own thread.
+static FINDER_QUEUE: LazyLock<Sender<(String, Configuration)>> = LazyLock::new(|| {
+    let (sender, receiver) = mpsc::channel::<(String, Configuration)>();
+    std::thread::spawn(move || {
+        for (id, config) in receiver {
+            run_job(&id, config);
+        }
+    });
+    sender
+});
+
+fn lock_jobs() -> MutexGuard<'static, VecDeque<FinderJob>> {
+    FINDER_JOBS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Queues a finder run, unless the same run is already waiting in the queue.
+pub fn queue_finder_job(description: String, config: Configuration) -> FinderJob {
+    let mut jobs = lock_jobs();
+    if let Some(job) = jobs
+        .iter()
+        .find(|job| job.state == FinderJobState::Queued && job.description == description)
+    {
+        return job.clone();
+    }
+
+    let job = FinderJob {
+        id: uuid::Uuid::new_v4().to_string(),
+        description,
+        state: FinderJobState::Queued,
+        queued: Utc::now(),
+        started: None,
+        finished: None,
+        total: 0,
+        processed: 0,
+        current_game: None,
+        current_file: None,
+        error: None,
+        results: Vec::new(),
+    };
+    jobs.push_front(job.clone());
+    remove_old_jobs(&mut jobs);
+    drop(jobs);
+
+    info!("Queued finder job {}: {}", job.id, job.description);
+    if FINDER_QUEUE.send((job.id.clone(), config)).is_err() {
+        warn!("Failed to queue finder job {}", job.id);
+        update_job(&job.id, |job| {
+            job.state = FinderJobState::Failed;
+            job.error = Some("The finder queue is closed".to_string());
+        });
+    }
+    job
+}
+
+pub fn get_finder_jobs() -> Vec<FinderJob> {
+    lock_jobs().iter().cloned().collect()
+}
+
+pub fn get_finder_job(id: &str) -> Option<FinderJob> {
+    lock_jobs().iter().find(|job| job.id == id).cloned()
+}
+
+fn remove_old_jobs(jobs: &mut VecDeque<FinderJob>) {
+    while jobs.len() > MAX_FINISHED_JOBS {
+        let Some(index) = jobs.iter().rposition(|job| {
+            matches!(job.state, FinderJobState::Finished | FinderJobState::Failed)
+        }) else {
+            break;
+        };
+        jobs.remove(index);
+    }
+}
+
+fn update_job(id: &str, update: impl FnOnce(&mut FinderJob)) {
+    if let Some(job) = lock_jobs().iter_mut().find(|job| job.id == id) {
+        update(job);
+    }
+}
+
+fn run_job(id: &str, config: Configuration) {
+    update_job(id, |job| {
+        job.state = FinderJobState::Running;
+        job.started = Some(Utc::now());
+    });
+    info!("Finder job {id} started");
+
+    let mut progress = JobProgress { id };
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        minus_games_finder::run_with_progress(config, &mut progress)
+    }))
+    .unwrap_or_else(|_| Err("The finder panicked".to_string()));
+
+    update_job(id, |job| {
+        job.finished = Some(Utc::now());
+        job.current_game = None;
+        job.current_file = None;
+        match result {
+            Ok(_) => job.state = FinderJobState::Finished,
+            Err(err) => {
+                warn!("Finder job {id} failed. Error: {err}");
+                job.state = FinderJobState::Failed;
+                job.error = Some(err);
+            }
+        }
+    });
+    info!("Finder job {id} finished");
+}
+
+struct JobProgress<'a> {
+    id: &'a str,
+}
+
+impl FinderProgress for JobProgress<'_> {
+    fn set_total(&mut self, total: usize) {
+        update_job(self.id, |job| job.total = total);
+    }
+
+    fn start_game(&mut self, game: &str) {
+        update_job(self.id, |job| {
+            job.current_game = Some(game.to_string());
+            job.current_file = None;
+        });
+    }
+
+    fn hash_file(&mut self, file: &Path) {
+        update_job(self.id, |job| {
+            job.current_file = Some(file.display().to_string())
+        });
+    }
+
+    fn finish_game(&mut self, result: FinderGameResult) {
+        update_job(self.id, |job| {
+            job.processed += 1;
+            job.current_game = None;
+            job.current_file = None;
+            job.results.push(result);
+        });
+    }
+}
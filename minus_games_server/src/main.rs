@@ -1,22 +1,24 @@
+use crate::actions::run_action;
 use crate::app_state::AppState;
 use crate::auth::auth_layer::AuthLayer;
-use crate::auth::session_manager::SessionManager;
+use crate::auth::session_manager::{SessionManager, remove_expired_sessions};
 use crate::auth::user::ArcUser;
 use crate::auth::user::User;
 use crate::auth::user_handler::UserHandler;
 use crate::configuration::Configuration;
 use crate::controller::{
-    client_controller, download_controller, finder_controller, game_controller, gui_controller,
-    sync_controller, updater_controller,
+    auth_controller, client_controller, download_controller, finder_controller, game_controller,
+    gui_controller, session_controller, sync_controller, sync_history_controller,
+    updater_controller, user_controller,
 };
 use crate::open_api::ApiDoc;
+use crate::sync_migration::migrate_legacy_folder_hashes;
 use axum::Router;
 use axum::http::StatusCode;
 use axum::http::header::CONTENT_TYPE;
 use axum::response::{IntoResponse, Redirect, Response};
 use axum::routing::get;
-use clap::Parser;
-use log::{debug, info};
+use log::{debug, error, info, warn};
 use mime::APPLICATION_JSON;
 use minus_games_models::other::Boolean;
 use std::sync::Arc;
@@ -29,11 +31,18 @@ use tracing_subscriber::EnvFilter;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
+mod actions;
 mod app_state;
 mod auth;
+#[cfg(test)]
+mod authorization_tests;
 mod configuration;
 mod controller;
+mod finder_jobs;
 mod open_api;
+mod sync_history;
+mod sync_migration;
+mod tls;
 mod utils;
 
 #[tokio::main]
@@ -42,25 +51,75 @@ async fn main() {
     let filter = EnvFilter::default().add_directive(LevelFilter::INFO.into());
     // .add_directive("minus_games_client=debug".parse().unwrap())
     // .add_directive("tower_http::trace=debug".parse().unwrap());
-    let config: Configuration = Configuration::parse();
-
     tracing_subscriber::fmt()
         .with_env_filter(filter)
         // .with_max_level(Level::INFO)
         .init();
 
-    info!("Config:\n{config}");
-    let addr = format!("{}:{}", config.ip, config.port);
+    let config: Configuration = match Configuration::load() {
+        Ok(config) => config,
+        Err(err) => {
+            error!("{err}");
+            std::process::exit(1);
+        }
+    };
+
     let user_files_path = config.data_folder.join("users");
     let user_handler = Arc::new(UserHandler { user_files_path });
-    let session_manager = Arc::new(RwLock::new(SessionManager::default()));
-    let clear_sessions = Arc::new(RwLock::new(None));
+    if let Some(action) = config.action.clone() {
+        let success = run_action(&user_handler, action);
+        std::process::exit(if success { 0 } else { 1 });
+    }
+
+    info!("Config:\n{config}");
+    migrate_legacy_folder_hashes(&config);
+    user_handler.check_users();
+    for conflict in minus_games_finder::find_library_conflicts(&config.get_libraries()) {
+        warn!(
+            "Game {} exists in the libraries: {}. Only the first one is used",
+            conflict.game,
+            conflict.libraries.join(", ")
+        );
+    }
+    if let Err(err) =
+        minus_games_finder::engine_descriptions::load_engine_descriptions(&config.data_folder)
+    {
+        error!("{err}");
+        std::process::exit(1);
+    }
+    let addr = format!("{}:{}", config.ip, config.port);
+    let rustls_config = match tls::create_rustls_config(&config).await {
+        Ok(rustls_config) => rustls_config,
+        Err(err) => {
+            error!("{err}");
+            std::process::exit(1);
+        }
+    };
+    if let Some(rustls_config) = rustls_config.clone()
+        && let Some(tls_cert) = config.tls_cert.clone()
+        && let Some(tls_key) = config.tls_key.clone()
+    {
+        tokio::spawn(tls::reload_on_change(rustls_config, tls_cert, tls_key));
+    }
+    let session_manager = Arc::new(RwLock::new(SessionManager::new(&config, &user_handler)));
+    tokio::spawn(remove_expired_sessions(session_manager.clone()));
     let app_state = Arc::new(AppState {
         config,
         user_handler,
         session_manager,
-        clear_sessions,
     });
+    if let Some(finder_interval) = app_state.config.finder_interval
+        && finder_interval > 0
+    {
+        tokio::spawn(finder_controller::run_finder_periodically(
+            app_state.clone(),
+            finder_interval,
+        ));
+    }
+
+    if app_state.config.watch {
+        finder_controller::watch_libraries(app_state.clone());
+    }
 
     // Service
     let service_layers = ServiceBuilder::new().layer(
@@ -70,9 +129,17 @@ async fn main() {
             .on_request(DefaultOnRequest::new().level(Level::TRACE)),
     );
 
-    let app = Router::new()
+    let app = create_router(app_state).await.layer(service_layers);
+
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    tls::serve(listener, app, rustls_config).await.unwrap();
+}
+
+async fn create_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
         .route("/health", get(health))
         .merge(me_route(app_state.clone()).await)
+        .nest("/auth", auth_controller::new_router(app_state.clone()))
         .nest(
             "/games",
             game_controller::new_router(app_state.clone()).await,
@@ -85,7 +152,16 @@ async fn main() {
             "/download",
             download_controller::new_router(app_state.clone()).await,
         )
+        .nest(
+            "/sessions",
+            session_controller::new_router(app_state.clone()),
+        )
         .nest("/sync", sync_controller::new_router(app_state.clone()))
+        .nest(
+            "/sync-history",
+            sync_history_controller::new_router(app_state.clone()),
+        )
+        .nest("/users", user_controller::new_router(app_state.clone()))
         .nest("/client", client_controller::new_router(app_state.clone()))
         .nest("/gui", gui_controller::new_router(app_state.clone()))
         .nest(
@@ -94,10 +170,6 @@ async fn main() {
         )
         .route("/", get(redirect_to_openapi))
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
-        .layer(service_layers);
-
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
 }
 
 async fn me_route(app_state: Arc<AppState>) -> Router {
@@ -107,7 +179,6 @@ async fn me_route(app_state: Arc<AppState>) -> Router {
         .layer(AuthLayer::new(
             app_state.user_handler.clone(),
             app_state.session_manager.clone(),
-            app_state.clear_sessions.clone(),
         ))
 }
 
@@ -8,13 +8,17 @@ use utoipauto::utoipauto;
 #[openapi(
     tags(
         (name = crate::TAG, description = "Main Controller Routes"),
+        (name = crate::controller::auth_controller::TAG, description = "Auth Controller Routes"),
         (name = crate::controller::game_controller::TAG, description = "Game Controller Routes"),
         (name = crate::controller::gui_controller::TAG, description = "Gui Controller Routes"),
         (name = crate::controller::client_controller::TAG, description = "Client Controller Routes"),
         (name = crate::controller::download_controller::TAG, description = "Downloader Controller Routes"),
         (name = crate::controller::finder_controller::TAG, description = "Finder Controller Routes"),
+        (name = crate::controller::session_controller::TAG, description = "Session Controller Routes"),
         (name = crate::controller::sync_controller::TAG, description = "Sync Controller Routes"),
-        (name = crate::controller::updater_controller::TAG, description = "Updater Controller Routes")
+        (name = crate::controller::sync_history_controller::TAG, description = "Sync History Controller Routes"),
+        (name = crate::controller::updater_controller::TAG, description = "Updater Controller Routes"),
+        (name = crate::controller::user_controller::TAG, description = "User Controller Routes")
     ),
     info(title = "Minus Games Server", description = "Minus Game Server"),
     modifiers(&SecurityAddon)
@@ -28,6 +32,10 @@ impl Modify for SecurityAddon {
         components.add_security_scheme(
             "basic-auth",
             SecurityScheme::Http(Http::new(HttpAuthScheme::Basic)),
+        );
+        components.add_security_scheme(
+            "bearer-auth",
+            SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
         )
     }
 }
@@ -0,0 +1,344 @@
+use chrono::{DateTime, Utc};
+use minus_games_models::sync_file_history::{SyncFileHistory, SyncFileRevision};
+use minus_games_utils::{create_file_list, set_file_modified_time};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::OwnedMutexGuard;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Temp files of uploads and restores, they are never listed as sync files.
+pub const UPLOAD_EXTENSION: &str = ".upload";
+
+static SYNC_FILE_LOCKS: LazyLock<Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Serializes the changes of a sync file, from checking the base version until the rename.
+pub async fn lock_sync_file(sync_file_path: &Path) -> OwnedMutexGuard<()> {
+    let lock = {
+        let mut locks = SYNC_FILE_LOCKS.lock().unwrap();
+        // Only the map holds the unused locks
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        locks
+            .entry(sync_file_path.to_path_buf())
+            .or_default()
+            .clone()
+    };
+    lock.lock_owned().await
+}
+
+/// A hidden temp file next to the sync file, which is renamed over it once it is complete.
+pub fn get_temp_path(sync_file_path: &Path) -> Option<PathBuf> {
+    let (parent, file_name) = (sync_file_path.parent()?, sync_file_path.file_name()?);
+    Some(parent.join(format!(
+        ".{}.{}{UPLOAD_EXTENSION}",
+        file_name.to_string_lossy(),
+        Uuid::new_v4()
+    )))
+}
+
+pub fn is_valid_revision(revision: &str) -> bool {
+    !revision.is_empty() && revision.chars().all(|c| c.is_ascii_digit())
+}
+
+fn create_revision_name() -> String {
+    Utc::now().format("%Y%m%d%H%M%S%6f").to_string()
+}
+
+pub async fn archive_sync_file(
+    sync_file_path: &Path,
+    history_file_folder: &Path,
+    history_size: usize,
+) {
+    if history_size == 0 || !sync_file_path.is_file() {
+        return;
+    }
+
+    if let Err(err) = tokio::fs::create_dir_all(history_file_folder).await {
+        warn!(
+            "Failed to create history folder: {} - {err}",
+            history_file_folder.display()
+        );
+        return;
+    }
+
+    let revision_path = history_file_folder.join(create_revision_name());
+    debug!(
+        "Archive {} to {}",
+        sync_file_path.display(),
+        revision_path.display()
+    );
+    // The sync file stays in place, until the new version is renamed over it
+    if tokio::fs::hard_link(sync_file_path, revision_path.as_path())
+        .await
+        .is_err()
+        && let Err(err) = copy_sync_file(sync_file_path, revision_path.as_path()).await
+    {
+        warn!(
+            "Failed to archive sync file: {} - {err}",
+            sync_file_path.display()
+        );
+        tokio::fs::remove_file(revision_path.as_path()).await.ok();
+        return;
+    }
+
+    trim_history(history_file_folder, history_size).await;
+}
+
+/// Fallback for file systems without hard links, the revision keeps the modified time.
+async fn copy_sync_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    let last_modified = tokio::fs::metadata(from).await?.modified()?;
+    tokio::fs::copy(from, to).await?;
+    set_file_modified_time(to, last_modified);
+    Ok(())
+}
+
+async fn trim_history(history_file_folder: &Path, history_size: usize) {
+    let mut revisions = get_revision_paths(history_file_folder);
+    if revisions.len() <= history_size {
+        return;
+    }
+
+    revisions.sort();
+    let to_remove = revisions.len() - history_size;
+    for revision in revisions.into_iter().take(to_remove) {
+        if let Err(err) = tokio::fs::remove_file(revision.as_path()).await {
+            warn!(
+                "Failed to remove old revision: {} - {err}",
+                revision.display()
+            );
+        }
+    }
+}
+
+fn get_revision_paths(history_file_folder: &Path) -> Vec<PathBuf> {
+    match history_file_folder.read_dir() {
+        Ok(read_dir) => read_dir
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Persists a rename into the folder of the path, Windows can not open folders for that.
+pub async fn sync_parent_folder(path: &Path) {
+    #[cfg(target_family = "unix")]
+    if let Some(parent) = path.parent() {
+        let result = match tokio::fs::File::open(parent).await {
+            Ok(folder) => folder.sync_all().await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            warn!("Failed to sync folder: {} - {err}", parent.display());
+        }
+    }
+    #[cfg(not(target_family = "unix"))]
+    let _ = path;
+}
+
+pub fn list_sync_history(history_folder: &Path) -> Vec<SyncFileHistory> {
+    if !history_folder.is_dir() {
+        return Vec::new();
+    }
+
+    let Ok(history_folder) = std::path::absolute(history_folder) else {
+        return Vec::new();
+    };
+    let cut_off = history_folder.iter().count();
+    let mut histories: BTreeMap<String, Vec<SyncFileRevision>> = BTreeMap::new();
+    for revision_path in create_file_list(history_folder.as_path()) {
+        let (Some(parent), Some(revision)) = (revision_path.parent(), revision_path.file_name())
+        else {
+            continue;
+        };
+        let Ok(metadata) = revision_path.metadata() else {
+            continue;
+        };
+        let file_path = parent
+            .iter()
+            .skip(cut_off)
+            .collect::<PathBuf>()
+            .to_string_lossy()
+            .replace("\\", "/");
+        let last_modified: DateTime<Utc> = match metadata.modified() {
+            Ok(modified) => modified.into(),
+            Err(_) => continue,
+        };
+
+        histories
+            .entry(file_path)
+            .or_default()
+            .push(SyncFileRevision {
+                revision: revision.to_string_lossy().to_string(),
+                size: metadata.len(),
+                last_modified,
+            });
+    }
+
+    histories
+        .into_iter()
+        .map(|(file_path, mut revisions)| {
+            revisions.sort_by(|a, b| b.revision.cmp(&a.revision));
+            SyncFileHistory {
+                file_path,
+                revisions,
+            }
+        })
+        .collect()
+}
+
+pub async fn restore_sync_file(
+    sync_file_path: &Path,
+    history_file_folder: &Path,
+    revision: &str,
+    history_size: usize,
+) -> std::io::Result<()> {
+    let revision_path = history_file_folder.join(revision);
+    let last_modified = tokio::fs::metadata(revision_path.as_path())
+        .await?
+        .modified()?;
+
+    if let Some(parent) = sync_file_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    // Copy first, so trimming the history can not remove the revision that gets restored.
+    let restore_path = get_temp_path(sync_file_path).ok_or(std::io::ErrorKind::InvalidInput)?;
+    if let Err(err) = tokio::fs::copy(revision_path.as_path(), restore_path.as_path()).await {
+        tokio::fs::remove_file(restore_path.as_path()).await.ok();
+        return Err(err);
+    }
+
+    let _lock = lock_sync_file(sync_file_path).await;
+    archive_sync_file(sync_file_path, history_file_folder, history_size.max(1)).await;
+
+    if let Err(err) = tokio::fs::rename(restore_path.as_path(), sync_file_path).await {
+        tokio::fs::remove_file(restore_path.as_path()).await.ok();
+        return Err(err);
+    }
+    set_file_modified_time(sync_file_path, last_modified);
+    sync_parent_folder(sync_file_path).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sync_history::{
+        archive_sync_file, copy_sync_file, get_revision_paths, get_temp_path, restore_sync_file,
+    };
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    /// Like an upload, the new version is renamed over the sync file.
+    fn upload(sync_file_path: &Path, content: &str) {
+        let temp_path = get_temp_path(sync_file_path).unwrap();
+        std::fs::write(temp_path.as_path(), content).unwrap();
+        std::fs::rename(temp_path, sync_file_path).unwrap();
+    }
+
+    fn read_revisions(history_folder: &Path) -> Vec<String> {
+        let mut revisions: Vec<PathBuf> = get_revision_paths(history_folder);
+        revisions.sort();
+        revisions
+            .iter()
+            .map(|revision| std::fs::read_to_string(revision).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_archive_trims_history_to_size() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sync_file_path = temp_dir.path().join("save.dat");
+        let history_folder = temp_dir.path().join("history");
+
+        for version in 1..=5 {
+            upload(&sync_file_path, &format!("v{version}"));
+            archive_sync_file(&sync_file_path, &history_folder, 3).await;
+        }
+
+        assert_eq!(read_revisions(&history_folder), ["v3", "v4", "v5"]);
+        assert_eq!(std::fs::read_to_string(&sync_file_path).unwrap(), "v5");
+    }
+
+    #[tokio::test]
+    async fn test_archive_without_history() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sync_file_path = temp_dir.path().join("save.dat");
+        let history_folder = temp_dir.path().join("history");
+
+        upload(&sync_file_path, "v1");
+        archive_sync_file(&sync_file_path, &history_folder, 0).await;
+
+        assert!(!history_folder.exists());
+    }
+
+    #[tokio::test]
+    async fn test_restore_revision_removed_by_trimming() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sync_file_path = temp_dir.path().join("saves").join("save.dat");
+        let history_folder = temp_dir.path().join("history");
+        std::fs::create_dir_all(sync_file_path.parent().unwrap()).unwrap();
+
+        for version in 1..=2 {
+            upload(&sync_file_path, &format!("v{version}"));
+            archive_sync_file(&sync_file_path, &history_folder, 2).await;
+        }
+        upload(&sync_file_path, "v3");
+        let mut revisions = get_revision_paths(&history_folder);
+        revisions.sort();
+        let oldest = revisions[0]
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        // Archiving v3 trims the revision, which gets restored
+        restore_sync_file(&sync_file_path, &history_folder, &oldest, 2)
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(&sync_file_path).unwrap(), "v1");
+        assert_eq!(read_revisions(&history_folder), ["v2", "v3"]);
+        let temp_files = std::fs::read_dir(sync_file_path.parent().unwrap())
+            .unwrap()
+            .count();
+        assert_eq!(temp_files, 1);
+    }
+
+    #[tokio::test]
+    async fn test_restore_unknown_revision() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sync_file_path = temp_dir.path().join("save.dat");
+        let history_folder = temp_dir.path().join("history");
+        upload(&sync_file_path, "v1");
+
+        assert!(
+            restore_sync_file(&sync_file_path, &history_folder, "1", 2)
+                .await
+                .is_err()
+        );
+        assert_eq!(std::fs::read_to_string(&sync_file_path).unwrap(), "v1");
+    }
+
+    #[tokio::test]
+    async fn test_copy_fallback_keeps_modified_time() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sync_file_path = temp_dir.path().join("save.dat");
+        let revision_path = temp_dir.path().join("revision");
+        upload(&sync_file_path, "v1");
+        let last_modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        minus_games_utils::set_file_modified_time(&sync_file_path, last_modified);
+
+        copy_sync_file(&sync_file_path, &revision_path)
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(&revision_path).unwrap(), "v1");
+        let modified = revision_path.metadata().unwrap().modified().unwrap();
+        assert_eq!(modified, last_modified);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::configuration::Configuration;
+use minus_games_utils::{create_hash_from_string, create_legacy_hash_from_string};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Renames sync folders, which still use the legacy folder hash, to the current folder hash.
+pub fn migrate_legacy_folder_hashes(config: &Configuration) {
+    let user_folders: Vec<PathBuf> = match config.data_folder.read_dir() {
+        Ok(read_dir) => read_dir
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect(),
+        Err(_) => return,
+    };
+
+    let mut migrated = 0;
+    for game in config.get_game_list() {
+        let Some(sync_folders) = config
+            .get_game_infos(&game)
+            .and_then(|game_infos| game_infos.sync_folders)
+        else {
+            continue;
+        };
+
+        for sync_folder in sync_folders {
+            let legacy_hash = create_legacy_hash_from_string(&sync_folder);
+            let folder_hash = create_hash_from_string(&sync_folder);
+            for user_folder in user_folders.iter() {
+                let Some(username) = user_folder.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                let folders = [
+                    (
+                        config.get_sync_folder_path(username, &game, &legacy_hash),
+                        config.get_sync_folder_path(username, &game, &folder_hash),
+                    ),
+                    (
+                        config.get_sync_history_folder_path(username, &game, &legacy_hash),
+                        config.get_sync_history_folder_path(username, &game, &folder_hash),
+                    ),
+                ];
+                for (from, to) in folders {
+                    if let (Some(from), Some(to)) = (from, to)
+                        && migrate_folder(from.as_path(), to.as_path())
+                    {
+                        migrated += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    if migrated > 0 {
+        info!("Migrated {migrated} sync folders to the current folder hash");
+    }
+}
+
+fn migrate_folder(from: &Path, to: &Path) -> bool {
+    if !from.is_dir() {
+        return false;
+    }
+
+    if to.exists() {
+        warn!(
+            "Can't migrate sync folder {}, because {} already exists",
+            from.display(),
+            to.display()
+        );
+        return false;
+    }
+
+    match std::fs::rename(from, to) {
+        Ok(_) => true,
+        Err(err) => {
+            warn!(
+                "Failed to migrate sync folder {} to {}: {err}",
+                from.display(),
+                to.display()
+            );
+            false
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::configuration::Configuration;
+use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+pub async fn create_rustls_config(config: &Configuration) -> Result<Option<RustlsConfig>, String> {
+    let (tls_cert, tls_key) = match (config.tls_cert.as_ref(), config.tls_key.as_ref()) {
+        (None, None) => return Ok(None),
+        (Some(tls_cert), Some(tls_key)) => (tls_cert, tls_key),
+        _ => return Err("TLS needs both a certificate and a key".to_string()),
+    };
+    // Fails if the provider is already installed
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    RustlsConfig::from_pem_file(tls_cert, tls_key)
+        .await
+        .map(Some)
+        .map_err(|err| format!("Failed to load the TLS certificate or key. Error: {err}"))
+}
+
+/// Reloads the certificate when the cert or key file changed, e.g. after a renewal.
+pub async fn reload_on_change(rustls_config: RustlsConfig, tls_cert: PathBuf, tls_key: PathBuf) {
+    let mut last_modified = get_last_modified(&tls_cert, &tls_key);
+    let mut interval = tokio::time::interval(RELOAD_CHECK_INTERVAL);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let modified = get_last_modified(&tls_cert, &tls_key);
+        if modified == last_modified {
+            continue;
+        }
+        match rustls_config
+            .reload_from_pem_file(&tls_cert, &tls_key)
+            .await
+        {
+            Ok(_) => {
+                info!("Reloaded the TLS certificate");
+                last_modified = modified;
+            }
+            Err(err) => warn!("Failed to reload the TLS certificate. Error: {err}"),
+        }
+    }
+}
+
+fn get_last_modified(tls_cert: &Path, tls_key: &Path) -> Option<(SystemTime, SystemTime)> {
+    let cert_modified = tls_cert.metadata().and_then(|m| m.modified()).ok()?;
+    let key_modified = tls_key.metadata().and_then(|m| m.modified()).ok()?;
+    Some((cert_modified, key_modified))
+}
+
+pub async fn serve(
+    listener: tokio::net::TcpListener,
+    app: Router,
+    rustls_config: Option<RustlsConfig>,
+) -> std::io::Result<()> {
+    match rustls_config {
+        Some(rustls_config) => {
+            axum_server::from_tcp_rustls(listener.into_std()?, rustls_config)
+                .serve(app.into_make_service())
+                .await
+        }
+        None => axum::serve(listener, app).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::configuration::Configuration;
+    use crate::tls::{create_rustls_config, serve};
+    use axum::Router;
+    use axum::routing::get;
+    use clap::Parser;
+    use minus_games_utils::tls::{configure_tls, create_fingerprint};
+    use rcgen::CertifiedKey;
+    use std::path::Path;
+
+    async fn start_tls_server(temp_dir: &Path) -> (u16, CertifiedKey) {
+        let certified_key = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+            .expect("Failed to generate certificate");
+        let tls_cert = temp_dir.join("cert.pem");
+        let tls_key = temp_dir.join("key.pem");
+        std::fs::write(&tls_cert, certified_key.cert.pem()).unwrap();
+        std::fs::write(&tls_key, certified_key.key_pair.serialize_pem()).unwrap();
+
+        let config = Configuration::parse_from([
+            "minus_games_server",
+            "--tls-cert",
+            tls_cert.to_str().unwrap(),
+            "--tls-key",
+            tls_key.to_str().unwrap(),
+        ]);
+        let rustls_config = create_rustls_config(&config).await.unwrap();
+        assert!(rustls_config.is_some());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let app = Router::new().route("/health", get(|| async { "OK" }));
+        tokio::spawn(serve(listener, app, rustls_config));
+        (port, certified_key)
+    }
+
+    async fn get_health(
+        url: &str,
+        ca_bundle: Option<&Path>,
+        cert_fingerprint: Option<&str>,
+    ) -> Result<String, reqwest::Error> {
+        let client = configure_tls(reqwest::ClientBuilder::new(), ca_bundle, cert_fingerprint)
+            .build()
+            .unwrap();
+        client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_self_signed_certificate() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let (port, certified_key) = start_tls_server(temp_dir.path()).await;
+        let url = format!("https://localhost:{port}/health");
+        let fingerprint = create_fingerprint(certified_key.cert.der());
+
+        assert!(get_health(&url, None, None).await.is_err());
+
+        let ca_bundle = temp_dir.path().join("cert.pem");
+        let body = get_health(&url, Some(&ca_bundle), None).await.unwrap();
+        assert_eq!(body, "OK");
+
+        let ip_url = format!("https://127.0.0.1:{port}/health");
+        let body = get_health(&ip_url, None, Some(&fingerprint)).await.unwrap();
+        assert_eq!(body, "OK");
+
+        let wrong_fingerprint = create_fingerprint(b"other");
+        assert!(
+            get_health(&url, None, Some(&wrong_fingerprint))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tls_needs_cert_and_key() {
+        let config = Configuration::parse_from(["minus_games_server", "--tls-cert", "cert.pem"]);
+        assert!(create_rustls_config(&config).await.is_err());
+    }
+}
@@ -1,12 +1,152 @@
-use crate::auth::user::ArcUser;
-use axum::extract::Request;
+use crate::auth::user::{ArcUser, Role};
+use axum::extract::{Request, State};
 use axum::http::StatusCode;
 use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
+use std::path::{Path, PathBuf};
 
-pub async fn super_user_only(user: ArcUser, request: Request, next: Next) -> Response {
-    if !user.is_superuser {
+pub async fn role_required(
+    State(role): State<Role>,
+    user: ArcUser,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !user.has_role(role) {
         return (StatusCode::FORBIDDEN, "User access forbidden").into_response();
     }
     next.run(request).await
 }
+
+/// Joins a user supplied relative path onto `base`.
+/// Returns `None` for absolute paths, `..` segments and paths which leave `base` through a symlink.
+pub fn safe_join(base: &Path, relative: &str) -> Option<PathBuf> {
+    if relative.starts_with(['/', '\\']) || relative.contains('\0') || has_drive_prefix(relative) {
+        return None;
+    }
+
+    let mut rtn = base.to_path_buf();
+    let mut is_empty = true;
+    for part in relative.split(['/', '\\']) {
+        match part {
+            "" | "." => continue,
+            ".." => return None,
+            _ => {
+                rtn.push(part);
+                is_empty = false;
+            }
+        }
+    }
+
+    if is_empty || escapes_base(base, rtn.as_path()) {
+        return None;
+    }
+
+    Some(rtn)
+}
+
+/// Like [`safe_join`], but `name` has to be a single path segment like a game or a folder hash.
+pub fn safe_join_name(base: &Path, name: &str) -> Option<PathBuf> {
+    if name.contains(['/', '\\']) {
+        return None;
+    }
+    safe_join(base, name)
+}
+
+fn has_drive_prefix(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+fn escapes_base(base: &Path, path: &Path) -> bool {
+    // If the base does not exist yet, nothing below it can be a symlink.
+    let Ok(canonical_base) = base.canonicalize() else {
+        return false;
+    };
+
+    let mut existing = path;
+    while existing.symlink_metadata().is_err() {
+        match existing.parent() {
+            Some(parent) => existing = parent,
+            None => return false,
+        }
+    }
+
+    match existing.canonicalize() {
+        Ok(canonical) => !canonical.starts_with(canonical_base),
+        // Dangling symlink
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{safe_join, safe_join_name};
+    use std::path::Path;
+
+    #[test]
+    fn test_safe_join_accepts_relative_paths() {
+        let base = Path::new("/data/user");
+        assert_eq!(
+            safe_join(base, "game/hash/save.dat"),
+            Some(base.join("game").join("hash").join("save.dat"))
+        );
+        assert_eq!(
+            safe_join(base, "./saves\\slot1.sav"),
+            Some(base.join("saves").join("slot1.sav"))
+        );
+        assert_eq!(
+            safe_join_name(base, "Some Game"),
+            Some(base.join("Some Game"))
+        );
+    }
+
+    #[test]
+    fn test_safe_join_rejects_malicious_paths() {
+        let base = Path::new("/data/user");
+        let malicious = [
+            "",
+            ".",
+            "..",
+            "../other_user/game/hash/save.dat",
+            "game/../../other_user",
+            "game/hash/../../../users/admin.json",
+            "..\\..\\users\\admin.json",
+            "game\\..\\..\\users",
+            "/etc/passwd",
+            "\\\\server\\share\\file",
+            "C:\\Windows\\win.ini",
+            "c:save.dat",
+            "game/\0/save.dat",
+        ];
+        for path in malicious {
+            assert_eq!(safe_join(base, path), None, "{path:?} was accepted");
+        }
+        assert_eq!(safe_join_name(base, "game/hash"), None);
+        assert_eq!(safe_join_name(base, "game\\hash"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_safe_join_rejects_symlink_escapes() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path().join("user");
+        let outside = root.path().join("outside");
+        std::fs::create_dir_all(base.join("game")).unwrap();
+        std::fs::create_dir_all(outside.as_path()).unwrap();
+        std::os::unix::fs::symlink(outside.as_path(), base.join("game").join("escape")).unwrap();
+        std::os::unix::fs::symlink(root.path().join("missing"), base.join("dangling")).unwrap();
+        std::os::unix::fs::symlink(base.join("game"), base.join("inside")).unwrap();
+
+        assert_eq!(safe_join(base.as_path(), "game/escape"), None);
+        assert_eq!(safe_join(base.as_path(), "game/escape/save.dat"), None);
+        assert_eq!(safe_join(base.as_path(), "dangling/save.dat"), None);
+        assert_eq!(
+            safe_join(base.as_path(), "inside/save.dat"),
+            Some(base.join("inside").join("save.dat"))
+        );
+        assert_eq!(
+            safe_join(base.as_path(), "game/new/save.dat"),
+            Some(base.join("game").join("new").join("save.dat"))
+        );
+    }
+}
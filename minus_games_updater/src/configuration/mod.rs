@@ -40,6 +40,12 @@ pub struct Configuration {
     pub username: Option<String>,
     #[arg(long, env = "MINUS_GAMES_PASSWORD")]
     pub password: Option<String>,
+    #[arg(long, env = "MINUS_GAMES_TOKEN")]
+    pub token: Option<String>,
+    #[arg(long, env = "MINUS_GAMES_CA_BUNDLE")]
+    pub ca_bundle: Option<PathBuf>,
+    #[arg(long, env = "MINUS_GAMES_CERT_FINGERPRINT")]
+    pub cert_fingerprint: Option<String>,
 }
 
 impl Display for Configuration {
@@ -6,6 +6,7 @@ use clap::Parser;
 use filetime::FileTime;
 use futures_util::stream::StreamExt;
 use minus_games_models::sync_file_info::SyncFileInfo;
+use minus_games_utils::tls::configure_tls;
 use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
 use reqwest::{Client, Url};
 #[cfg(target_family = "unix")]
@@ -35,26 +36,30 @@ async fn main() {
     info!("Config:\n{config}");
 
     // Init Client
-    let client = if let Some(username) = config.username.as_ref()
+    let authorization = if let Some(token) = config.token.as_ref() {
+        Some(format!("Bearer {token}"))
+    } else if let Some(username) = config.username.as_ref()
         && let Some(password) = config.password.as_ref()
     {
-        let mut headers = HeaderMap::new();
         let encoded_part = BASE64_STANDARD.encode(format!("{}:{}", username, password));
+        Some(format!("Basic {encoded_part}"))
+    } else {
+        None
+    };
+    let mut client_builder = configure_tls(
+        reqwest::ClientBuilder::new().cookie_store(true),
+        config.ca_bundle.as_deref(),
+        config.cert_fingerprint.as_deref(),
+    );
+    if let Some(authorization) = authorization {
+        let mut headers = HeaderMap::new();
         headers.append(
             AUTHORIZATION,
-            HeaderValue::from_str(&format!("Basic {encoded_part}")).unwrap(),
+            HeaderValue::from_str(&authorization).unwrap(),
         );
-        reqwest::ClientBuilder::new()
-            .cookie_store(true)
-            .default_headers(headers)
-            .build()
-            .unwrap()
-    } else {
-        reqwest::ClientBuilder::new()
-            .cookie_store(true)
-            .build()
-            .unwrap()
-    };
+        client_builder = client_builder.default_headers(headers);
+    }
+    let client = client_builder.build().unwrap();
 
     let url = match Url::parse(config.server_url.as_str()) {
         Ok(url) => url,
@@ -1,3 +1,5 @@
 pub const ADDITIONS: &str = "additions";
 pub const INFOS: &str = "infos";
 pub const HEADER_JPG: &str = "header.jpg";
+pub const SYNC_HISTORY: &str = ".history";
+pub const SCUMMVM_SAVES: &str = "scummvm_saves";
@@ -1,5 +1,7 @@
 use crate::constants::{ADDITIONS, HEADER_JPG, INFOS};
-use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use argon2::password_hash::SaltString;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use clap::builder::OsStr;
 use filetime::set_file_mtime;
 use std::hash::{DefaultHasher, Hasher};
@@ -9,6 +11,8 @@ use std::time::SystemTime;
 use walkdir::WalkDir;
 
 pub mod constants;
+#[cfg(feature = "tls")]
+pub mod tls;
 
 pub struct CacheFolder {}
 
@@ -88,17 +92,31 @@ impl From<ClientFolder> for OsStr {
     }
 }
 
+const FOLDER_HASH_VERSION: &str = "v1";
+
+/// Stable folder hash, prefixed with the version of the hashing scheme.
 pub fn create_hash_from_string(value: &str) -> String {
+    let hash = blake3::hash(value.as_bytes());
+    let number = u64::from_le_bytes(hash.as_bytes()[..8].try_into().unwrap());
+    format!("{FOLDER_HASH_VERSION}-{}", encode_base62(number))
+}
+
+/// Folder hash used before [`create_hash_from_string`]. `DefaultHasher` is not stable across Rust releases,
+/// so this is only kept to migrate existing sync folders.
+pub fn create_legacy_hash_from_string(value: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(value.as_bytes());
+    encode_base62(hasher.finish())
+}
+
+fn encode_base62(mut number: u64) -> String {
     const CHARS: [char; 62] = [
         '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h',
         'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
         'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R',
         'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
     ];
-    let mut hasher = DefaultHasher::new();
-    hasher.write(value.as_bytes());
-    let mut number = hasher.finish();
-    let mut rtn = String::with_capacity(6);
+    let mut rtn = String::with_capacity(11);
     while number != 0 {
         let idx = (number % 62) as usize;
         rtn.push(CHARS[idx]);
@@ -145,6 +163,18 @@ pub fn verify_argon2_hash(password: &str, hash: &str) -> bool {
         .is_ok()
 }
 
+pub fn create_argon2_hash(password: &str) -> Option<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .ok()
+        .map(|hash| hash.to_string())
+}
+
+pub fn is_argon2_hash(hash: &str) -> bool {
+    PasswordHash::new(hash).is_ok()
+}
+
 pub fn create_game_infos_name(game: &str) -> String {
     format!("{game}.json")
 }
@@ -161,6 +191,10 @@ pub fn create_last_time_played_name(game: &str) -> String {
     format!("{game}.played")
 }
 
+pub fn create_sync_base_versions_name(game: &str) -> String {
+    format!("{game}.sync")
+}
+
 pub fn get_game_infos_path(data_dir: &Path, game: &str) -> PathBuf {
     data_dir.join(INFOS).join(create_game_infos_name(game))
 }
@@ -179,18 +213,24 @@ pub fn get_last_time_played_path(data_dir: &Path, game: &str) -> PathBuf {
         .join(create_last_time_played_name(game))
 }
 
+pub fn get_sync_base_versions_path(data_dir: &Path, game: &str) -> PathBuf {
+    data_dir
+        .join(INFOS)
+        .join(create_sync_base_versions_name(game))
+}
+
 pub fn get_header_path(data_dir: PathBuf, game: &str) -> PathBuf {
     data_dir.join(ADDITIONS).join(game).join(HEADER_JPG)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::create_hash_from_string;
+    use crate::{create_hash_from_string, create_legacy_hash_from_string};
 
     #[test]
     fn test_create_hash_from_string() {
         let value = "test";
-        let hash = create_hash_from_string(value);
-        assert_eq!(hash, "tMSBYrhFthj");
+        assert_eq!(create_legacy_hash_from_string(value), "tMSBYrhFthj");
+        assert_eq!(create_hash_from_string(value), "v1-I0dDALvCWtl");
     }
 }
@@ -0,0 +1,138 @@
+use reqwest::{Certificate, ClientBuilder};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, verify_tls12_signature, verify_tls13_signature};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, Error, SignatureScheme};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Trusts the certificates of a custom CA bundle or pins the sha256 fingerprint
+/// of the server certificate, for servers with a self-signed certificate.
+/// A pinned fingerprint replaces the CA and hostname check.
+pub fn configure_tls(
+    mut builder: ClientBuilder,
+    ca_bundle: Option<&Path>,
+    cert_fingerprint: Option<&str>,
+) -> ClientBuilder {
+    if let Some(ca_bundle) = ca_bundle {
+        match read_ca_bundle(ca_bundle) {
+            Some(certificates) => {
+                for certificate in certificates {
+                    builder = builder.add_root_certificate(certificate);
+                }
+            }
+            None => warn!("Failed to read the CA bundle: {}", ca_bundle.display()),
+        }
+    }
+
+    if let Some(cert_fingerprint) = cert_fingerprint {
+        let Some(fingerprint) = parse_fingerprint(cert_fingerprint) else {
+            warn!("Invalid certificate fingerprint: {cert_fingerprint}");
+            return builder;
+        };
+        match create_pinned_client_config(fingerprint) {
+            Ok(client_config) => builder = builder.use_preconfigured_tls(client_config),
+            Err(err) => warn!("Failed to create the TLS config. Error: {err}"),
+        }
+    }
+    builder
+}
+
+fn read_ca_bundle(ca_bundle: &Path) -> Option<Vec<Certificate>> {
+    let pem = std::fs::read(ca_bundle).ok()?;
+    Certificate::from_pem_bundle(&pem).ok()
+}
+
+/// Accepts the sha256 fingerprint in hex, with or without colons.
+pub fn parse_fingerprint(value: &str) -> Option<Vec<u8>> {
+    let hex: String = value.chars().filter(|c| *c != ':').collect();
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+pub fn create_fingerprint(certificate_der: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, certificate_der)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<String>>()
+        .join(":")
+}
+
+fn create_pinned_client_config(fingerprint: Vec<u8>) -> Result<ClientConfig, Error> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = FingerprintVerifier {
+        fingerprint,
+        provider: provider.clone(),
+    };
+    Ok(ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth())
+}
+
+#[derive(Debug)]
+struct FingerprintVerifier {
+    fingerprint: Vec<u8>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for FingerprintVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, Error> {
+        let digest = ring::digest::digest(&ring::digest::SHA256, end_entity.as_ref());
+        if digest.as_ref() != self.fingerprint.as_slice() {
+            return Err(Error::General(
+                "Certificate fingerprint does not match".to_string(),
+            ));
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}